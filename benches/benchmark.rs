@@ -4,11 +4,22 @@ use criterion::{Criterion, criterion_group, criterion_main};
 fn day_benches(c: &mut Criterion) {
     let mut group = c.benchmark_group("days");
     DAYS.iter().for_each(|day| {
+        // Days without input are skipped rather than failing the whole run
+        if day.input().is_err() {
+            return;
+        }
+        group.bench_function(format!("{}_parse", day.name).as_str(), |b| {
+            b.iter(|| day.parse().is_ok());
+        });
+        // Parts can only be benchmarked on input that parses
+        let Ok(parsed) = day.parse() else {
+            return;
+        };
         group.bench_function(format!("{}_part1", day.name).as_str(), |b| {
-            b.iter(|| day.part1());
+            b.iter(|| parsed.part1());
         });
         group.bench_function(format!("{}_part2", day.name).as_str(), |b| {
-            b.iter(|| day.part2());
+            b.iter(|| parsed.part2());
         });
     });
     group.finish();
@@ -18,7 +29,7 @@ fn all_benches(c: &mut Criterion) {
     c.bench_function("all", |b| {
         b.iter(|| {
             DAYS.iter()
-                .map(|day| (day.part1(), day.part2()))
+                .map(|day| day.parse().map(|parsed| (parsed.part1(), parsed.part2())))
                 .collect::<Vec<_>>()
         });
     });
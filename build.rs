@@ -0,0 +1,52 @@
+// Finds every day module in src/days and generates the list of days that src/days.rs registers, so
+// a new day only needs its file
+use std::{env, fs, path::Path};
+
+fn is_day(name: &str) -> bool {
+    name.len() == 3 && name.starts_with('d') && name[1..].bytes().all(|b| b.is_ascii_digit())
+}
+
+fn main() {
+    println!("cargo::rerun-if-changed=src/days");
+    println!("cargo::rerun-if-changed=data");
+
+    let mut days: Vec<_> = fs::read_dir("src/days")
+        .expect("src/days should be readable")
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            let day = name.strip_suffix(".rs")?;
+            is_day(day).then(|| day.to_string())
+        })
+        .collect();
+    days.sort();
+
+    // Inputs are fetched separately, so these are only warnings
+    for day in &days {
+        if !Path::new(&format!("data/{day}.txt")).exists() {
+            println!("cargo::warning=src/days/{day}.rs has no input in data/{day}.txt");
+        }
+    }
+    if let Ok(entries) = fs::read_dir("data") {
+        for entry in entries.filter_map(Result::ok) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if let Some(day) = name.strip_suffix(".txt").filter(|day| is_day(day))
+                && !days.iter().any(|d| d == day)
+            {
+                println!("cargo::warning=data/{name} has no module in src/days/{day}.rs");
+            }
+        }
+    }
+
+    let dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src/days");
+    let entries: Vec<_> = days
+        .iter()
+        .map(|day| {
+            // Debug formatting quotes and escapes the path as a string literal
+            let path = dir.join(format!("{day}.rs")).display().to_string();
+            format!("{day} = {path:?}")
+        })
+        .collect();
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("days.rs");
+    fs::write(out, format!("register_days!({});\n", entries.join(", ")))
+        .expect("the list of days should be writable");
+}
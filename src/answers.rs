@@ -0,0 +1,122 @@
+use std::{collections::HashMap, fmt};
+
+use crate::days::{Answer, read_input};
+
+pub const ANSWERS_FILE: &str = "data/answers.toml";
+
+/// Known correct answers, keyed by day and part. The file has a table per day:
+///
+/// ```toml
+/// [d01]
+/// part1 = 1034
+/// part2 = "6166"
+/// ```
+///
+/// Answers can be integers or strings, strings allow for values too large for TOML integers
+#[derive(Debug, Default)]
+pub struct Answers(HashMap<(String, u8), String>);
+
+/// The result of comparing an answer against the known one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    Unknown,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected } => write!(f, "fail, expected {expected}"),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl Answers {
+    pub fn load(filename: &str) -> Result<Self, String> {
+        read_input(filename)
+            .and_then(|text| Self::parse(&text))
+            .map_err(|e| format!("{filename}: {e}"))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut answers = HashMap::new();
+        for (day, parts) in table {
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| format!("{day} should be a table of parts"))?;
+            for (key, value) in parts {
+                let part = match key.as_str() {
+                    "part1" => 1,
+                    "part2" => 2,
+                    _ => return Err(format!("{day}.{key} is not part1 or part2")),
+                };
+                let answer = match value {
+                    toml::Value::Integer(n) => n.to_string(),
+                    toml::Value::String(s) => s.clone(),
+                    _ => return Err(format!("{day}.{key} should be an integer or string")),
+                };
+                answers.insert((day.clone(), part), answer);
+            }
+        }
+        Ok(Self(answers))
+    }
+
+    pub fn expected(&self, day: &str, part: u8) -> Option<&str> {
+        self.0.get(&(day.to_string(), part)).map(String::as_str)
+    }
+
+    /// Checks an answer, where `None` is a part that could not be solved
+    pub fn check(&self, day: &str, part: u8, answer: Option<&Answer>) -> Verdict {
+        match (self.expected(day, part), answer) {
+            (None, _) => Verdict::Unknown,
+            (Some(expected), Some(answer)) if answer.to_string() == expected => Verdict::Pass,
+            (Some(expected), _) => Verdict::Fail {
+                expected: expected.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+[d01]
+part1 = 3
+part2 = "6"
+
+[d02]
+part1 = 1227775554
+"#;
+
+    #[test]
+    fn verdicts() {
+        let answers = Answers::parse(EXAMPLE).unwrap();
+        assert_eq!(answers.check("d01", 1, Some(&3u16.into())), Verdict::Pass);
+        assert_eq!(answers.check("d01", 2, Some(&6u64.into())), Verdict::Pass);
+        assert_eq!(
+            answers.check("d02", 1, Some(&1u64.into())),
+            Verdict::Fail {
+                expected: "1227775554".to_string()
+            }
+        );
+        assert!(matches!(
+            answers.check("d02", 1, None),
+            Verdict::Fail { .. }
+        ));
+        assert_eq!(
+            answers.check("d02", 2, Some(&1u64.into())),
+            Verdict::Unknown
+        );
+    }
+
+    #[test]
+    fn invalid_part() {
+        assert!(Answers::parse("[d01]\npart3 = 1\n").is_err());
+    }
+}
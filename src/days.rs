@@ -1,57 +1,391 @@
-use std::{fs, sync::LazyLock};
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, Read},
+    marker::PhantomData,
+    sync::{LazyLock, OnceLock},
+};
 
+use parse::ParseError;
+
+pub mod crypt;
+pub mod parse;
 pub mod utils;
 
-type PartFn = fn(&str) -> i64;
+/// The answer to one part of a puzzle. Most answers are integers of some width, but anything that
+/// can be typed into the answer box on the website can be represented
+#[derive(Debug, Clone)]
+pub enum Answer {
+    Signed(i64),
+    Unsigned(u64),
+    Wide(u128),
+    Text(String),
+}
+
+impl Answer {
+    // Sign and magnitude of numeric answers, so that answers of different widths can be compared
+    fn numeric(&self) -> Option<(bool, u128)> {
+        match *self {
+            Answer::Signed(x) => Some((x < 0, x.unsigned_abs().into())),
+            Answer::Unsigned(x) => Some((false, x.into())),
+            Answer::Wide(x) => Some((false, x)),
+            Answer::Text(_) => None,
+        }
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Answer::Text(a), Answer::Text(b)) => a == b,
+            _ => self.numeric().is_some() && self.numeric() == other.numeric(),
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(x) => x.fmt(f),
+            Answer::Unsigned(x) => x.fmt(f),
+            Answer::Wide(x) => x.fmt(f),
+            Answer::Text(x) => x.fmt(f),
+        }
+    }
+}
+
+macro_rules! impl_answer_from {
+    ($variant:ident, $target:ty, $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(x: $t) -> Self {
+                    Answer::$variant(<$target>::from(x))
+                }
+            }
+        )*
+    };
+}
+
+impl_answer_from!(Signed, i64, i8, i16, i32, i64);
+impl_answer_from!(Unsigned, u64, u8, u16, u32, u64);
+impl_answer_from!(Wide, u128, u128);
+impl_answer_from!(Text, String, String, &str);
+
+impl From<usize> for Answer {
+    fn from(x: usize) -> Self {
+        Answer::Unsigned(x.try_into().unwrap())
+    }
+}
+
+/// The step of solving a day that an error came from
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stage {
+    Input,
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Input => write!(f, "input"),
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part1 => write!(f, "part 1"),
+            Stage::Part2 => write!(f, "part 2"),
+        }
+    }
+}
+
+/// A failure to solve one part of a day, located within that day's input where possible
+#[derive(Debug, Clone)]
+pub struct SolveError {
+    pub day: String,
+    pub stage: Stage,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub text: String,
+    pub message: String,
+}
+
+impl SolveError {
+    fn new(day: &str, stage: Stage, input: &str, err: ParseError) -> Self {
+        let (line, column) = err.locate(input).unzip();
+        Self {
+            day: day.to_string(),
+            stage,
+            line,
+            column,
+            text: err.text,
+            message: err.message,
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Only show the start of the offending text, it can be most of the input
+        const MAX_LEN: usize = 40;
+
+        write!(f, "{} {}", self.day, self.stage)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " at line {line}, column {column}")?;
+        }
+        let first_line = self.text.lines().next().unwrap_or_default();
+        let text: String = first_line.chars().take(MAX_LEN).collect();
+        let ellipsis = if text.len() < self.text.len() {
+            "..."
+        } else {
+            ""
+        };
+        write!(f, ": {} ({text:?}{ellipsis})", self.message)
+    }
+}
+
+impl Error for SolveError {}
+
+/// A solution to a day's puzzle. The input is parsed once and the result is shared between both
+/// parts, which allows parsing to be timed separately from solving
+pub trait Solution {
+    type Parsed<'a>;
+    type Part1: Into<Answer>;
+    type Part2: Into<Answer>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError>;
+    fn part1(parsed: &Self::Parsed<'_>) -> Result<Self::Part1, ParseError>;
+    fn part2(parsed: &Self::Parsed<'_>) -> Result<Self::Part2, ParseError>;
+
+    fn solve_part1(input: &str) -> Result<Self::Part1, ParseError> {
+        Self::part1(&Self::parse(input)?)
+    }
+
+    fn solve_part2(input: &str) -> Result<Self::Part2, ParseError> {
+        Self::part2(&Self::parse(input)?)
+    }
+}
+
+/// Object safe version of [`Solution`], so that days with different parsed types can be stored
+/// together in the registry
+pub trait DynSolution: Sync {
+    fn parse_input<'a>(&self, input: &'a str) -> Result<Box<dyn ParsedInput + 'a>, ParseError>;
+}
+
+pub trait ParsedInput {
+    fn part1(&self) -> Result<Answer, ParseError>;
+    fn part2(&self) -> Result<Answer, ParseError>;
+}
+
+struct ParsedWith<'a, S: Solution>(S::Parsed<'a>, PhantomData<S>);
+
+impl<S: Solution> ParsedInput for ParsedWith<'_, S> {
+    fn part1(&self) -> Result<Answer, ParseError> {
+        S::part1(&self.0).map(Into::into)
+    }
+
+    fn part2(&self) -> Result<Answer, ParseError> {
+        S::part2(&self.0).map(Into::into)
+    }
+}
+
+impl<S: Solution + Sync + 'static> DynSolution for S {
+    fn parse_input<'a>(&self, input: &'a str) -> Result<Box<dyn ParsedInput + 'a>, ParseError> {
+        Ok(Box::new(ParsedWith::<S>(S::parse(input)?, PhantomData)))
+    }
+}
+
+// Reads an input or answers file, decrypting it in memory if it is still encrypted. A filename of
+// "-" reads stdin
+pub(crate) fn read_input(filename: &str) -> Result<String, String> {
+    let mut data = if filename == "-" {
+        let mut data = Vec::new();
+        io::stdin()
+            .read_to_end(&mut data)
+            .map_err(|e| e.to_string())?;
+        data
+    } else {
+        fs::read(filename).map_err(|e| e.to_string())?
+    };
+    if crypt::is_encrypted(&data) {
+        data = crypt::Key::from_env()
+            .and_then(|key| key.decrypt(&data))
+            .map_err(|e| e.to_string())?;
+    }
+    String::from_utf8(data).map_err(|_| "input is not valid UTF-8".to_string())
+}
 
 pub struct Day {
     pub name: String,
-    input: String,
-    part1_impl: PartFn,
-    part2_impl: PartFn,
+    pub filename: String,
+    // Read on first use, so that a missing file only affects its own day
+    input: OnceLock<Result<String, String>>,
+    solution: &'static dyn DynSolution,
 }
 
 impl Day {
-    fn new(name: &str, filename: &str, part1: PartFn, part2: PartFn) -> Self {
+    fn new(name: &str, filename: &str, solution: &'static dyn DynSolution) -> Self {
         Day {
             name: name.to_string(),
-            input: fs::read_to_string(filename).unwrap(),
-            part1_impl: part1,
-            part2_impl: part2,
+            filename: filename.to_string(),
+            input: OnceLock::new(),
+            solution,
         }
     }
 
+    /// The same day solving the input in `filename` instead of its own, where "-" is stdin
     #[must_use]
-    pub fn part1(&self) -> i64 {
-        (self.part1_impl)(&self.input)
+    pub fn with_input(&self, filename: &str) -> Day {
+        Day::new(&self.name, filename, self.solution)
     }
 
-    #[must_use]
-    pub fn part2(&self) -> i64 {
-        (self.part2_impl)(&self.input)
+    pub fn number(&self) -> u32 {
+        // Names always come from the module names in the registry
+        self.name.trim_start_matches('d').parse().unwrap()
+    }
+
+    pub fn input(&self) -> Result<&str, SolveError> {
+        let input = self.input.get_or_init(|| read_input(&self.filename));
+        match input {
+            Ok(input) => Ok(input),
+            Err(message) => Err(SolveError {
+                day: self.name.clone(),
+                stage: Stage::Input,
+                line: None,
+                column: None,
+                text: self.filename.clone(),
+                message: message.clone(),
+            }),
+        }
+    }
+
+    pub fn parse(&self) -> Result<ParsedDay<'_>, SolveError> {
+        let input = self.input()?;
+        let parsed = self
+            .solution
+            .parse_input(input)
+            .map_err(|e| SolveError::new(&self.name, Stage::Parse, input, e))?;
+        Ok(ParsedDay {
+            name: &self.name,
+            input,
+            parsed,
+        })
     }
 }
 
-macro_rules! declare_modules {
-    ($($day:ident),*) => {
+/// A day with its input already parsed, ready to solve either part
+pub struct ParsedDay<'a> {
+    name: &'a str,
+    input: &'a str,
+    parsed: Box<dyn ParsedInput + 'a>,
+}
+
+impl ParsedDay<'_> {
+    fn locate(&self, stage: Stage, err: ParseError) -> SolveError {
+        SolveError::new(self.name, stage, self.input, err)
+    }
+
+    pub fn part1(&self) -> Result<Answer, SolveError> {
+        self.parsed
+            .part1()
+            .map_err(|e| self.locate(Stage::Part1, e))
+    }
+
+    pub fn part2(&self) -> Result<Answer, SolveError> {
+        self.parsed
+            .part2()
+            .map_err(|e| self.locate(Stage::Part2, e))
+    }
+}
+
+macro_rules! register_days {
+    ($($day:ident = $path:literal),*) => {
         $(
+            #[path = $path]
             pub mod $day;
         )*
-    };
-}
 
-macro_rules! make_days {
-    ($($day:ident),*) => {
-        {
+        pub static DAYS: LazyLock<Vec<Day>> = LazyLock::new(|| {
             vec![
                 $(
-                    Day::new(stringify!($day), concat!("data/", stringify!($day), ".txt"), $day::part1, $day::part2),
+                    Day::new(
+                        stringify!($day),
+                        concat!("data/", stringify!($day), ".txt"),
+                        &$day::Solver,
+                    ),
                 )*
             ]
-        }
+        });
     };
 }
 
-declare_modules!(d01, d02, d03, d04, d05, d06, d08, d09, d10, d11);
-pub static DAYS: LazyLock<Vec<Day>> =
-    LazyLock::new(|| make_days!(d01, d02, d03, d04, d05, d06, d08, d09, d10, d11));
+// The build script lists every src/days/dNN.rs as `register_days!(d01 = "/.../d01.rs", ...);`. The
+// paths are absolute, as modules declared in an included file are looked for next to that file
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_compare_across_widths() {
+        assert_eq!(Answer::from(42u16), Answer::from(42i64));
+        assert_eq!(Answer::from(42usize), Answer::from(42u128));
+        assert_ne!(Answer::from(-42i64), Answer::from(42u64));
+        assert_ne!(Answer::from(42u64), Answer::from("42"));
+    }
+
+    #[test]
+    fn answers_display() {
+        assert_eq!(Answer::from(-7i32).to_string(), "-7");
+        assert_eq!(Answer::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(Answer::from("abc,def").to_string(), "abc,def");
+    }
+
+    #[test]
+    fn solve_error_location() {
+        let input = "L68\nLx30\n";
+        let day = Day {
+            input: OnceLock::from(Ok(input.to_string())),
+            ..Day::new("d01", "", &d01::Solver)
+        };
+        let err = day.parse().err().unwrap();
+        assert_eq!((err.day.as_str(), err.stage), ("d01", Stage::Parse));
+        assert_eq!((err.line, err.column), (Some(2), Some(2)));
+        assert_eq!(err.text, "x30");
+    }
+
+    #[test]
+    fn missing_input() {
+        let day = Day::new("d01", "data/missing.txt", &d01::Solver);
+        let err = day.parse().err().unwrap();
+        assert_eq!(err.stage, Stage::Input);
+        assert_eq!(err.text, "data/missing.txt");
+    }
+
+    #[test]
+    fn input_override() {
+        let path = std::env::temp_dir().join(format!("aoc25-input-{}.txt", std::process::id()));
+        fs::write(
+            &path,
+            "L68
+L30
+R48
+L5
+R60
+L55
+L1
+L99
+R14
+L82
+",
+        )
+        .unwrap();
+        let day = Day::new("d01", "data/missing.txt", &d01::Solver);
+        let day = day.with_input(path.to_str().unwrap());
+        let parsed = day.parse().unwrap();
+        assert_eq!(parsed.part1().unwrap(), Answer::from(3u64));
+        assert_eq!(parsed.part2().unwrap(), Answer::from(6u64));
+        fs::remove_file(path).unwrap();
+    }
+}
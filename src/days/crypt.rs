@@ -0,0 +1,311 @@
+use std::{env, error::Error, fmt, fs, io, str::FromStr};
+
+use aes::Aes256;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+// Inputs are committed encrypted by transcrypt, which runs `openssl enc -aes-256-cbc -a`. The
+// output is "Salted__", an 8 byte salt and the ciphertext, base64 encoded with line breaks
+const MAGIC: &[u8] = b"Salted__";
+const MAGIC_BASE64: &[u8] = b"U2FsdGVkX1";
+const SALT_LEN: usize = 8;
+const KEY_LEN: usize = 32;
+const IV_LEN: usize = 16;
+// openssl breaks base64 output into lines of this length
+const LINE_LEN: usize = 64;
+
+pub const PASSPHRASE_VAR: &str = "AOC25_PASSPHRASE";
+pub const KEYFILE_VAR: &str = "AOC25_KEYFILE";
+pub const KDF_VAR: &str = "AOC25_KDF";
+
+/// How the key and IV are derived from the passphrase, matching the `-md` and `-pbkdf2` options of
+/// `openssl enc`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Kdf {
+    /// `EVP_BytesToKey` with MD5, which is what transcrypt uses
+    #[default]
+    Md5,
+    /// `EVP_BytesToKey` with SHA-256, the default of `openssl enc` without `-pbkdf2`
+    Sha256,
+    /// PBKDF2 with HMAC-SHA-256
+    Pbkdf2 { iterations: u32 },
+}
+
+impl FromStr for Kdf {
+    type Err = CryptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Same default iteration count as openssl
+        const DEFAULT_ITERATIONS: u32 = 10_000;
+
+        match s.split_once(':') {
+            None if s == "md5" => Ok(Kdf::Md5),
+            None if s == "sha256" => Ok(Kdf::Sha256),
+            None if s == "pbkdf2" => Ok(Kdf::Pbkdf2 {
+                iterations: DEFAULT_ITERATIONS,
+            }),
+            Some(("pbkdf2", n)) => n
+                .parse()
+                .map(|iterations| Kdf::Pbkdf2 { iterations })
+                .map_err(|_| CryptError::Kdf(s.to_string())),
+            _ => Err(CryptError::Kdf(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CryptError {
+    NoPassphrase,
+    Keyfile(String, io::Error),
+    Kdf(String),
+    Base64(base64::DecodeError),
+    Truncated,
+    Decrypt,
+    Salt(getrandom::Error),
+}
+
+impl fmt::Display for CryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptError::NoPassphrase => {
+                write!(f, "no passphrase, set {PASSPHRASE_VAR} or {KEYFILE_VAR}")
+            }
+            CryptError::Keyfile(path, e) => write!(f, "could not read keyfile {path:?}: {e}"),
+            CryptError::Kdf(s) => write!(
+                f,
+                "unknown key derivation {s:?}, expected md5, sha256, pbkdf2 or pbkdf2:<iterations>"
+            ),
+            CryptError::Base64(e) => write!(f, "invalid base64: {e}"),
+            CryptError::Truncated => write!(f, "encrypted data is truncated"),
+            CryptError::Decrypt => write!(f, "could not decrypt, the passphrase is probably wrong"),
+            CryptError::Salt(e) => write!(f, "could not generate salt: {e}"),
+        }
+    }
+}
+
+impl Error for CryptError {}
+
+/// A passphrase along with how to turn it into a key
+pub struct Key {
+    passphrase: Vec<u8>,
+    kdf: Kdf,
+}
+
+impl Key {
+    pub fn new(passphrase: impl Into<Vec<u8>>, kdf: Kdf) -> Self {
+        Self {
+            passphrase: passphrase.into(),
+            kdf,
+        }
+    }
+
+    /// Reads the passphrase from `AOC25_PASSPHRASE`, or failing that from the file named by
+    /// `AOC25_KEYFILE`. The key derivation can be chosen with `AOC25_KDF`
+    pub fn from_env() -> Result<Self, CryptError> {
+        let kdf = env::var(KDF_VAR).map_or(Ok(Kdf::default()), |s| s.parse())?;
+        if let Some(passphrase) = env::var_os(PASSPHRASE_VAR) {
+            return Ok(Self::new(passphrase.into_encoded_bytes(), kdf));
+        }
+        let path = env::var(KEYFILE_VAR).map_err(|_| CryptError::NoPassphrase)?;
+        let mut passphrase = fs::read(&path).map_err(|e| CryptError::Keyfile(path, e))?;
+        // Editors like to add a trailing newline, which would never be meant as part of the key
+        while passphrase.last().is_some_and(|&b| b == b'\n' || b == b'\r') {
+            passphrase.pop();
+        }
+        Ok(Self::new(passphrase, kdf))
+    }
+
+    fn derive(&self, salt: &[u8]) -> ([u8; KEY_LEN], [u8; IV_LEN]) {
+        let mut bytes = [0; KEY_LEN + IV_LEN];
+        match self.kdf {
+            Kdf::Md5 => bytes_to_key::<Md5>(&self.passphrase, salt, &mut bytes),
+            Kdf::Sha256 => bytes_to_key::<Sha256>(&self.passphrase, salt, &mut bytes),
+            Kdf::Pbkdf2 { iterations } => {
+                pbkdf2::pbkdf2_hmac::<Sha256>(&self.passphrase, salt, iterations, &mut bytes);
+            }
+        }
+        let (key, iv) = bytes.split_at(KEY_LEN);
+        (key.try_into().unwrap(), iv.try_into().unwrap())
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, CryptError> {
+        let data = if data.starts_with(MAGIC) {
+            data.to_vec()
+        } else {
+            let text: Vec<u8> = data
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            STANDARD.decode(text).map_err(CryptError::Base64)?
+        };
+        let (salt, ciphertext) = data
+            .strip_prefix(MAGIC)
+            .filter(|rest| rest.len() >= SALT_LEN)
+            .ok_or(CryptError::Truncated)?
+            .split_at(SALT_LEN);
+
+        let (key, iv) = self.derive(salt);
+        cbc::Decryptor::<Aes256>::new(&key.into(), &iv.into())
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .map_err(|_| CryptError::Decrypt)
+    }
+
+    /// Encrypts in the same base64 format as `openssl enc -a`, with a random salt
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<String, CryptError> {
+        let mut salt = [0; SALT_LEN];
+        getrandom::fill(&mut salt).map_err(CryptError::Salt)?;
+        Ok(self.encrypt_with_salt(plaintext, salt))
+    }
+
+    fn encrypt_with_salt(&self, plaintext: &[u8], salt: [u8; SALT_LEN]) -> String {
+        let (key, iv) = self.derive(&salt);
+        let ciphertext = cbc::Encryptor::<Aes256>::new(&key.into(), &iv.into())
+            .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+        let data = [MAGIC, &salt, &ciphertext].concat();
+
+        let encoded = STANDARD.encode(data);
+        let mut text = String::with_capacity(encoded.len() + encoded.len() / LINE_LEN + 1);
+        for line in encoded.as_bytes().chunks(LINE_LEN) {
+            // base64 is always ASCII
+            text.push_str(str::from_utf8(line).unwrap());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+// OpenSSL's EVP_BytesToKey with a single iteration. Each block is the hash of the previous block,
+// the passphrase and the salt
+fn bytes_to_key<D: Digest>(passphrase: &[u8], salt: &[u8], out: &mut [u8]) {
+    let mut block = Vec::new();
+    for chunk in out.chunks_mut(<D as Digest>::output_size()) {
+        block = D::new()
+            .chain_update(&block)
+            .chain_update(passphrase)
+            .chain_update(salt)
+            .finalize()
+            .to_vec();
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
+/// Whether `data` is in the format written by `openssl enc`, either raw or base64 encoded
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC) || data.starts_with(MAGIC_BASE64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Produced by `openssl enc -aes-256-cbc -a -pass pass:hunter2` with the options in each test
+    const PLAINTEXT: &str = "L68\nL30\n";
+
+    fn decrypt(data: &str, kdf: Kdf) -> Result<String, CryptError> {
+        Key::new("hunter2", kdf)
+            .decrypt(data.as_bytes())
+            .map(|bytes| String::from_utf8(bytes).unwrap())
+    }
+
+    #[test]
+    fn md5() {
+        let data = "U2FsdGVkX1+rni9cP/+Trx8SVpteQvV8Y9a7cbtEJVk=\n";
+        assert_eq!(decrypt(data, Kdf::Md5).unwrap(), PLAINTEXT);
+    }
+
+    #[test]
+    fn sha256() {
+        let data = "U2FsdGVkX1+OSJ9SUO4KS3gZpczcXE4nKuCbY3pgXVg=\n";
+        assert_eq!(decrypt(data, Kdf::Sha256).unwrap(), PLAINTEXT);
+    }
+
+    #[test]
+    fn pbkdf2() {
+        let data = "U2FsdGVkX19SLzef3w2E8tqvaaS/eI6JEIr4skkQNTo=\n";
+        assert_eq!(decrypt(data, "pbkdf2".parse().unwrap()).unwrap(), PLAINTEXT);
+        let data = "U2FsdGVkX1+aqqce4dPfS2lPlO42Ckxh2kvpGywckGc=\n";
+        assert_eq!(
+            decrypt(data, "pbkdf2:1000".parse().unwrap()).unwrap(),
+            PLAINTEXT
+        );
+    }
+
+    #[test]
+    fn multiple_lines() {
+        let data = "
+U2FsdGVkX18ODDn1qSI31u433VOYLDM/4RfZYdKy1Lxg1Veafwuwp6OnVSK3WFEU
+A9VotsJMFIG9+AQgWk7rbQ==
+";
+        let result = decrypt(data.trim_start(), Kdf::Md5).unwrap();
+        assert_eq!(result, "3-5\n10-14\n16-20\n12-18\n\n1\n5\n8\n11\n17\n32\n");
+    }
+
+    #[test]
+    fn raw() {
+        let data = STANDARD
+            .decode("U2FsdGVkX1+rni9cP/+Trx8SVpteQvV8Y9a7cbtEJVk=")
+            .unwrap();
+        assert!(is_encrypted(&data));
+        let result = Key::new("hunter2", Kdf::Md5).decrypt(&data).unwrap();
+        assert_eq!(result, PLAINTEXT.as_bytes());
+    }
+
+    // Encrypting with the salt from openssl's output must reproduce that output exactly
+    fn encrypt(expected: &str, plaintext: &str, kdf: Kdf) {
+        let data = STANDARD.decode(expected.replace('\n', "")).unwrap();
+        let salt = data[MAGIC.len()..][..SALT_LEN].try_into().unwrap();
+        let result = Key::new("hunter2", kdf).encrypt_with_salt(plaintext.as_bytes(), salt);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn encrypt_matches_openssl() {
+        encrypt(
+            "U2FsdGVkX1+rni9cP/+Trx8SVpteQvV8Y9a7cbtEJVk=\n",
+            PLAINTEXT,
+            Kdf::Md5,
+        );
+        encrypt(
+            "U2FsdGVkX1+OSJ9SUO4KS3gZpczcXE4nKuCbY3pgXVg=\n",
+            PLAINTEXT,
+            Kdf::Sha256,
+        );
+        encrypt(
+            "U2FsdGVkX1+aqqce4dPfS2lPlO42Ckxh2kvpGywckGc=\n",
+            PLAINTEXT,
+            Kdf::Pbkdf2 { iterations: 1000 },
+        );
+        encrypt(
+            "U2FsdGVkX18ODDn1qSI31u433VOYLDM/4RfZYdKy1Lxg1Veafwuwp6OnVSK3WFEU\nA9VotsJMFIG9+AQgWk7rbQ==\n",
+            "3-5\n10-14\n16-20\n12-18\n\n1\n5\n8\n11\n17\n32\n",
+            Kdf::Md5,
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let key = Key::new("hunter2", Kdf::Md5);
+        let salt = [1, 2, 3, 4, 5, 6, 7, 8];
+        let plaintext = "a".repeat(100);
+        let data = key.encrypt_with_salt(plaintext.as_bytes(), salt);
+        assert!(data.lines().all(|line| line.len() <= LINE_LEN));
+        assert_eq!(key.decrypt(data.as_bytes()).unwrap(), plaintext.as_bytes());
+    }
+
+    #[test]
+    fn wrong_passphrase() {
+        let data = "U2FsdGVkX1+rni9cP/+Trx8SVpteQvV8Y9a7cbtEJVk=\n";
+        assert!(matches!(
+            Key::new("hunter3", Kdf::Md5).decrypt(data.as_bytes()),
+            Err(CryptError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn plaintext() {
+        assert!(!is_encrypted(PLAINTEXT.as_bytes()));
+    }
+}
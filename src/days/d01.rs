@@ -1,46 +1,59 @@
-pub fn part1(input: &str) -> i64 {
-    input
-        .lines()
-        .fold((50_i16, 0_u16), |(dial, zero_count), line| {
-            let (dir, count_str) = line.split_at(1);
-            let count = count_str.parse::<i16>().expect("could not parse count");
-            let dial_unwrapped = if dir.as_bytes()[0] == b'R' {
-                dial + count
-            } else {
-                dial - count
-            };
-
-            let dial = dial_unwrapped.rem_euclid(100);
-
-            (dial, zero_count + u16::from(dial == 0))
-        })
-        .1
-        .into()
+use super::{
+    Solution,
+    parse::{ParseError, parse},
+};
+
+fn parse_rotation(line: &str) -> Result<i16, ParseError> {
+    let (dir, count_str) = line
+        .split_at_checked(1)
+        .ok_or_else(|| ParseError::new(line, "expected a rotation"))?;
+    let count = parse::<i16>(count_str)?;
+    match dir {
+        "R" => Ok(count),
+        "L" => Ok(-count),
+        _ => Err(ParseError::new(dir, "invalid direction")),
+    }
 }
 
-pub fn part2(input: &str) -> i64 {
-    input
-        .lines()
-        .fold((50_i16, 0_u16), |(dial, zero_count), line| {
-            let (dir, count_str) = line.split_at(1);
-            let count = count_str.parse::<i16>().expect("could not parse count");
-            let dial_unwrapped = if dir.as_bytes()[0] == b'R' {
-                dial + count
-            } else {
-                dial - count
-            };
-
-            let div = dial_unwrapped.div_euclid(100);
-            let rem = dial_unwrapped.rem_euclid(100);
-            let moving_left_to_zero = div <= 0 && rem == 0;
-            let moving_left_from_zero = div < 0 && dial == 0;
-            let inc =
-                div.unsigned_abs() + u16::from(moving_left_to_zero) - u16::from(moving_left_from_zero);
-
-            (rem, zero_count + inc)
-        })
-        .1
-        .into()
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<i16>;
+    type Part1 = u16;
+    type Part2 = u16;
+
+    fn parse(input: &str) -> Result<Vec<i16>, ParseError> {
+        input.lines().map(parse_rotation).collect()
+    }
+
+    fn part1(rotations: &Vec<i16>) -> Result<u16, ParseError> {
+        Ok(rotations
+            .iter()
+            .fold((50_i16, 0_u16), |(dial, zero_count), rotation| {
+                let dial = (dial + rotation).rem_euclid(100);
+
+                (dial, zero_count + u16::from(dial == 0))
+            })
+            .1)
+    }
+
+    fn part2(rotations: &Vec<i16>) -> Result<u16, ParseError> {
+        Ok(rotations
+            .iter()
+            .fold((50_i16, 0_u16), |(dial, zero_count), rotation| {
+                let dial_unwrapped = dial + rotation;
+
+                let div = dial_unwrapped.div_euclid(100);
+                let rem = dial_unwrapped.rem_euclid(100);
+                let moving_left_to_zero = div <= 0 && rem == 0;
+                let moving_left_from_zero = div < 0 && dial == 0;
+                let inc = div.unsigned_abs() + u16::from(moving_left_to_zero)
+                    - u16::from(moving_left_from_zero);
+
+                (rem, zero_count + inc)
+            })
+            .1)
+    }
 }
 
 #[cfg(test)]
@@ -62,61 +75,67 @@ L82
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 3);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 6);
     }
 
     #[test]
     fn wrapping_r() {
-        let result = part2("R1000");
+        let result = Solver::solve_part2("R1000").unwrap();
         assert_eq!(result, 10);
     }
 
     #[test]
     fn wrapping_l() {
-        let result = part2("L1000");
+        let result = Solver::solve_part2("L1000").unwrap();
         assert_eq!(result, 10);
     }
 
     #[test]
     fn landing_on_100() {
-        let result = part2("R50");
+        let result = Solver::solve_part2("R50").unwrap();
         assert_eq!(result, 1);
     }
 
     #[test]
     fn landing_on_0() {
-        let result = part2("L50");
+        let result = Solver::solve_part2("L50").unwrap();
         assert_eq!(result, 1);
     }
 
     #[test]
     fn landing_on_200() {
-        let result = part2("R150");
+        let result = Solver::solve_part2("R150").unwrap();
         assert_eq!(result, 2);
     }
 
     #[test]
     fn landing_on_neg_100() {
-        let result = part2("L150");
+        let result = Solver::solve_part2("L150").unwrap();
         assert_eq!(result, 2);
     }
 
     #[test]
     fn landing_on_neg_200() {
-        let result = part2("L250");
+        let result = Solver::solve_part2("L250").unwrap();
         assert_eq!(result, 3);
     }
 
     #[test]
     fn to_zero_then_99() {
-        let result = part2("L50\nL1");
+        let result = Solver::solve_part2("L50\nL1").unwrap();
         assert_eq!(result, 1);
     }
+
+    #[test]
+    fn invalid_direction() {
+        let result = Solver::parse("L50\nU1");
+        assert_eq!(result.unwrap_err().text, "U");
+    }
 }
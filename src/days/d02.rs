@@ -1,104 +1,133 @@
 use std::collections::HashSet;
 
-pub fn part1(input: &str) -> i64 {
+use super::{
+    Solution,
+    parse::{ParseError, parse, split_once},
+};
+
+fn parse_number(s: &str) -> Result<&str, ParseError> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::new(s, "expected a number"));
+    }
+    parse::<u64>(s)?;
+    Ok(s)
+}
+
+// Ranges are left as strings as the digits are needed. They are validated up front so that any
+// substrings of them can be parsed without failing later
+fn parse_ranges(input: &str) -> Result<Vec<(&str, &str)>, ParseError> {
     input
         .trim()
         .split(',')
-        .flat_map(|r| {
-            let (low_s, high_s) = r.split_once('-').unwrap();
-
-            ((low_s.len())..=high_s.len())
-                .filter(|l| l % 2 == 0)
-                .map(|l| {
-                    let seq_len = l / 2;
-
-                    let seq_low = if l == low_s.len() {
-                        let (upper_s, lower_s) = low_s.split_at(seq_len);
-                        let upper = upper_s.parse().unwrap();
-                        let lower: u64 = lower_s.parse().unwrap();
-                        if lower > upper { upper + 1 } else { upper }
-                    } else {
-                        10u64.pow((seq_len - 1).try_into().unwrap())
-                    };
-                    let seq_high = if l == high_s.len() {
-                        let (upper_s, lower_s) = high_s.split_at(seq_len);
-                        let upper = upper_s.parse().unwrap();
-                        let lower: u64 = lower_s.parse().unwrap();
-                        if upper > lower { upper - 1 } else { upper }
-                    } else {
-                        10u64.pow(seq_len.try_into().unwrap()) - 1
-                    };
-
-                    if seq_high < seq_low {
-                        0
-                    } else {
-                        let subseq_sum = (seq_high - seq_low + 1) * (seq_low + seq_high) / 2;
-                        subseq_sum * (10u64.pow(seq_len.try_into().unwrap())) + subseq_sum
-                    }
-                })
+        .map(|r| {
+            let (low_s, high_s) = split_once(r, "-")?;
+            Ok((parse_number(low_s)?, parse_number(high_s)?))
         })
-        .sum::<u64>()
-        .try_into()
-        .unwrap()
+        .collect()
 }
 
-pub fn part2(input: &str) -> i64 {
-    let mut set = HashSet::<u64>::new();
+pub struct Solver;
 
-    input
-        .trim()
-        .split(',')
-        .map(|range| {
-            let (low_s, high_s) = range.split_once('-').unwrap();
-            let low: u64 = low_s.parse().unwrap();
-            let high: u64 = high_s.parse().unwrap();
-
-            let iter = (low_s.len()..=high_s.len()).flat_map(|l| {
-                (1..=(l / 2))
-                    .filter(move |seq_len| l % seq_len == 0)
-                    .flat_map(move |seq_len| {
-                        let seq_low = if l == low_s.len() {
-                            let upper = low_s[..seq_len].parse::<u64>().unwrap();
+impl Solution for Solver {
+    type Parsed<'a> = Vec<(&'a str, &'a str)>;
+    type Part1 = u64;
+    type Part2 = u64;
 
-                            let upper_total: u64 = (0..(l / seq_len))
-                                .map(|e| upper * (10u64.pow((e * seq_len).try_into().unwrap())))
-                                .sum();
+    fn parse(input: &str) -> Result<Vec<(&str, &str)>, ParseError> {
+        parse_ranges(input)
+    }
+
+    fn part1(ranges: &Vec<(&str, &str)>) -> Result<u64, ParseError> {
+        Ok(ranges
+            .iter()
+            .flat_map(|&(low_s, high_s)| {
+                ((low_s.len())..=high_s.len())
+                    .filter(|l| l % 2 == 0)
+                    .map(|l| {
+                        let seq_len = l / 2;
 
-                            if upper_total >= low { upper } else { upper + 1 }
+                        let seq_low = if l == low_s.len() {
+                            let (upper_s, lower_s) = low_s.split_at(seq_len);
+                            let upper = upper_s.parse().unwrap();
+                            let lower: u64 = lower_s.parse().unwrap();
+                            if lower > upper { upper + 1 } else { upper }
                         } else {
                             10u64.pow((seq_len - 1).try_into().unwrap())
                         };
                         let seq_high = if l == high_s.len() {
-                            let upper = high_s[..seq_len].parse::<u64>().unwrap();
-
-                            let upper_total: u64 = (0..(l / seq_len))
-                                .map(|e| upper * (10u64.pow((e * seq_len).try_into().unwrap())))
-                                .sum();
-
-                            if upper_total <= high {
-                                upper
-                            } else {
-                                upper - 1
-                            }
+                            let (upper_s, lower_s) = high_s.split_at(seq_len);
+                            let upper = upper_s.parse().unwrap();
+                            let lower: u64 = lower_s.parse().unwrap();
+                            if upper > lower { upper - 1 } else { upper }
                         } else {
-                            10u64.pow((seq_len).try_into().unwrap()) - 1
+                            10u64.pow(seq_len.try_into().unwrap()) - 1
                         };
 
-                        (seq_low..=seq_high).map(move |seq| {
-                            (0..(l / seq_len))
-                                .map(|e| seq * (10u64.pow((e * seq_len).try_into().unwrap())))
-                                .sum::<u64>()
-                        })
+                        if seq_high < seq_low {
+                            0
+                        } else {
+                            let subseq_sum = (seq_high - seq_low + 1) * (seq_low + seq_high) / 2;
+                            subseq_sum * (10u64.pow(seq_len.try_into().unwrap())) + subseq_sum
+                        }
                     })
-            });
+            })
+            .sum())
+    }
 
-            set.clear();
-            set.extend(iter);
-            set.iter().sum::<u64>()
-        })
-        .sum::<u64>()
-        .try_into()
-        .unwrap()
+    fn part2(ranges: &Vec<(&str, &str)>) -> Result<u64, ParseError> {
+        let mut set = HashSet::<u64>::new();
+
+        Ok(ranges
+            .iter()
+            .map(|&(low_s, high_s)| {
+                let low: u64 = low_s.parse().unwrap();
+                let high: u64 = high_s.parse().unwrap();
+
+                let iter = (low_s.len()..=high_s.len()).flat_map(|l| {
+                    (1..=(l / 2))
+                        .filter(move |seq_len| l % seq_len == 0)
+                        .flat_map(move |seq_len| {
+                            let seq_low = if l == low_s.len() {
+                                let upper = low_s[..seq_len].parse::<u64>().unwrap();
+
+                                let upper_total: u64 = (0..(l / seq_len))
+                                    .map(|e| upper * (10u64.pow((e * seq_len).try_into().unwrap())))
+                                    .sum();
+
+                                if upper_total >= low { upper } else { upper + 1 }
+                            } else {
+                                10u64.pow((seq_len - 1).try_into().unwrap())
+                            };
+                            let seq_high = if l == high_s.len() {
+                                let upper = high_s[..seq_len].parse::<u64>().unwrap();
+
+                                let upper_total: u64 = (0..(l / seq_len))
+                                    .map(|e| upper * (10u64.pow((e * seq_len).try_into().unwrap())))
+                                    .sum();
+
+                                if upper_total <= high {
+                                    upper
+                                } else {
+                                    upper - 1
+                                }
+                            } else {
+                                10u64.pow((seq_len).try_into().unwrap()) - 1
+                            };
+
+                            (seq_low..=seq_high).map(move |seq| {
+                                (0..(l / seq_len))
+                                    .map(|e| seq * (10u64.pow((e * seq_len).try_into().unwrap())))
+                                    .sum::<u64>()
+                            })
+                        })
+                });
+
+                set.clear();
+                set.extend(iter);
+                set.iter().sum::<u64>()
+            })
+            .sum())
+    }
 }
 
 #[cfg(test)]
@@ -111,55 +140,55 @@ mod tests {
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 1227775554);
     }
 
     #[test]
     fn p1_11_22() {
-        let result = part1("11-22");
+        let result = Solver::solve_part1("11-22").unwrap();
         assert_eq!(result, 33);
     }
 
     #[test]
     fn p1_998_1012() {
-        let result = part1("998-1012");
+        let result = Solver::solve_part1("998-1012").unwrap();
         assert_eq!(result, 1010);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 4174379265);
     }
 
     #[test]
     fn p2_998_1012() {
-        let result = part2("998-1012");
+        let result = Solver::solve_part2("998-1012").unwrap();
         assert_eq!(result, 2009);
     }
 
     #[test]
     fn p2_565653_565659() {
-        let result = part2("565653-565659");
+        let result = Solver::solve_part2("565653-565659").unwrap();
         assert_eq!(result, 565656);
     }
 
     #[test]
     fn p2_222220_222224() {
-        let result = part2("222220-222224");
+        let result = Solver::solve_part2("222220-222224").unwrap();
         assert_eq!(result, 222222);
     }
 
     #[test]
     fn p2_201950_202021() {
-        let result = part2("201950-202121");
+        let result = Solver::solve_part2("201950-202121").unwrap();
         assert_eq!(result, 202020);
     }
 
     #[test]
     fn p2_446443_446449() {
-        let result = part2("446443-446449");
+        let result = Solver::solve_part2("446443-446449").unwrap();
         assert_eq!(result, 446446);
     }
 }
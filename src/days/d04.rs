@@ -2,88 +2,106 @@ use std::iter::zip;
 
 use ndarray::{Array2, Zip, s};
 
+use super::{Solution, parse::ParseError};
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Val {
+pub enum Val {
     Roll,
     Open,
 }
 
 use Val::{Open, Roll};
 
-fn build_grid(input: &str) -> (Array2<Val>, [usize; 2]) {
-    let ncols = input.lines().next().unwrap().len();
-    let nrows = ((input.len() + 1) / ncols) - 1;
+fn build_grid(input: &str) -> Result<(Array2<Val>, [usize; 2]), ParseError> {
+    let ncols = input
+        .lines()
+        .next()
+        .ok_or_else(|| ParseError::new(input, "empty input"))?
+        .len();
+    let nrows = input.lines().count();
 
     let mut grid = Array2::from_elem((nrows + 2, ncols + 2), Open);
 
-    for (x, elem) in zip(
-        grid.slice_mut(s![1..(1 + nrows), 1..(1 + ncols)]),
-        input.chars().filter_map(|c| match c {
-            '@' => Some(Roll),
-            '.' => Some(Open),
-            _ => None,
-        }),
-    ) {
-        *x = elem;
+    let mut inner = grid.slice_mut(s![1..(1 + nrows), 1..(1 + ncols)]);
+    for (mut row, line) in zip(inner.rows_mut(), input.lines()) {
+        if line.len() != ncols {
+            return Err(ParseError::new(line, format!("expected {ncols} columns")));
+        }
+        for (x, (i, c)) in zip(&mut row, line.char_indices()) {
+            *x = match c {
+                '@' => Roll,
+                '.' => Open,
+                _ => return Err(ParseError::new(&line[i..], "invalid tile")),
+            };
+        }
     }
 
-    (grid, [nrows, ncols])
+    Ok((grid, [nrows, ncols]))
 }
 
-pub fn part1(input: &str) -> i64 {
-    build_grid(input)
-        .0
-        .windows((3, 3))
-        .into_iter()
-        .filter(|&w| w[(1, 1)] == Roll && w.into_iter().filter(|&&x| x == Roll).count() <= 4)
-        .count()
-        .try_into()
-        .unwrap()
-}
+pub struct Solver;
 
-// TODO: I'm unhappy with performance here. Many passes of large arrays. Possible refactor:
-// - Values stored as enum of { Empty, Roll(count) }
-// - Iterative passes remove rolls that have counts less than 4, and when removed also decrement the
-//   counts of the neighbours
-// - This will most likely reduce the number of iterations required.
-// - It may be challenging to enforce count invariant. Not quite sure how initialisation would go,
-//   maybe with an intermediate array of bools?
-// - Possibly could done in a single pass if we recursively remove the rolls. i.e. when a count is
-//   reduced below the threshold as part of the removal of another roll, then it also triggers
-//   reduction of its neighbours. This may be more complex to implement though
-pub fn part2(input: &str) -> i64 {
-    let (mut grid, inner_shape) = build_grid(input);
-    let mut to_be_removed: Array2<bool> = Array2::default(inner_shape);
-    let mut count = 0;
-    loop {
-        to_be_removed.fill(false);
-        Zip::from(grid.windows((3, 3)))
-            .and(&mut to_be_removed)
-            .for_each(|w, to_remove| {
-                if w[(1, 1)] == Roll && w.into_iter().filter(|&&x| x == Roll).count() <= 4 {
-                    *to_remove = true;
-                }
-            });
-
-        let inc_count = to_be_removed
-            .iter()
-            .filter_map(|&x| x.then_some(()))
-            .count();
-
-        count += inc_count;
-
-        if inc_count == 0 {
-            return count.try_into().unwrap();
-        }
+impl Solution for Solver {
+    type Parsed<'a> = (Array2<Val>, [usize; 2]);
+    type Part1 = usize;
+    type Part2 = usize;
 
-        #[expect(clippy::reversed_empty_ranges)]
-        Zip::from(grid.slice_mut(s![1..-1, 1..-1]))
-            .and(&to_be_removed)
-            .for_each(|v, &to_remove| {
-                if to_remove {
-                    *v = Open;
-                }
-            });
+    fn parse(input: &str) -> Result<(Array2<Val>, [usize; 2]), ParseError> {
+        build_grid(input)
+    }
+
+    fn part1((grid, _): &(Array2<Val>, [usize; 2])) -> Result<usize, ParseError> {
+        Ok(grid
+            .windows((3, 3))
+            .into_iter()
+            .filter(|&w| w[(1, 1)] == Roll && w.into_iter().filter(|&&x| x == Roll).count() <= 4)
+            .count())
+    }
+
+    // TODO: I'm unhappy with performance here. Many passes of large arrays. Possible refactor:
+    // - Values stored as enum of { Empty, Roll(count) }
+    // - Iterative passes remove rolls that have counts less than 4, and when removed also decrement
+    //   the counts of the neighbours
+    // - This will most likely reduce the number of iterations required.
+    // - It may be challenging to enforce count invariant. Not quite sure how initialisation would
+    //   go, maybe with an intermediate array of bools?
+    // - Possibly could done in a single pass if we recursively remove the rolls. i.e. when a count
+    //   is reduced below the threshold as part of the removal of another roll, then it also
+    //   triggers reduction of its neighbours. This may be more complex to implement though
+    fn part2((grid, inner_shape): &(Array2<Val>, [usize; 2])) -> Result<usize, ParseError> {
+        let mut grid = grid.clone();
+        let mut to_be_removed: Array2<bool> = Array2::default(*inner_shape);
+        let mut count = 0;
+        loop {
+            to_be_removed.fill(false);
+            Zip::from(grid.windows((3, 3)))
+                .and(&mut to_be_removed)
+                .for_each(|w, to_remove| {
+                    if w[(1, 1)] == Roll && w.into_iter().filter(|&&x| x == Roll).count() <= 4 {
+                        *to_remove = true;
+                    }
+                });
+
+            let inc_count = to_be_removed
+                .iter()
+                .filter_map(|&x| x.then_some(()))
+                .count();
+
+            count += inc_count;
+
+            if inc_count == 0 {
+                return Ok(count);
+            }
+
+            #[expect(clippy::reversed_empty_ranges)]
+            Zip::from(grid.slice_mut(s![1..-1, 1..-1]))
+                .and(&to_be_removed)
+                .for_each(|v, &to_remove| {
+                    if to_remove {
+                        *v = Open;
+                    }
+                });
+        }
     }
 }
 
@@ -106,13 +124,13 @@ mod tests {
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 13);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 43);
     }
 }
@@ -1,57 +1,60 @@
 use itertools::Itertools;
 
-pub fn part1(input: &str) -> i64 {
-    let (fresh_ranges, available) = input.split_once("\n\n").unwrap();
+use super::{
+    Solution,
+    parse::{ParseError, parse, split_once},
+};
 
-    let fresh_ranges: Vec<_> = fresh_ranges
+fn parse_ranges(fresh_ranges: &str) -> Result<Vec<(u64, u64)>, ParseError> {
+    fresh_ranges
         .lines()
         .map(|l| {
-            let (low, high) = l.split_once('-').unwrap();
-            low.parse::<u64>().unwrap()..=high.parse::<u64>().unwrap()
+            let (low, high) = split_once(l, "-")?;
+            Ok((parse(low)?, parse(high)?))
         })
-        .collect();
-
-    available
-        .lines()
-        .filter(|&l| {
-            let x: u64 = l.parse().unwrap();
-            fresh_ranges.iter().any(|r| r.contains(&x))
-        })
-        .count()
-        .try_into()
-        .unwrap()
+        .collect()
 }
 
-pub fn part2(input: &str) -> i64 {
-    let (fresh_ranges, _) = input.split_once("\n\n").unwrap();
+pub struct Solver;
 
-    let fresh_ranges: Vec<_> = fresh_ranges
-        .lines()
-        .map(|l| {
-            let (low, high) = l.split_once('-').unwrap();
-            (low.parse::<u64>().unwrap(), high.parse::<u64>().unwrap())
-        })
-        .sorted()
-        .collect();
+impl Solution for Solver {
+    type Parsed<'a> = (Vec<(u64, u64)>, Vec<u64>);
+    type Part1 = usize;
+    type Part2 = u64;
 
-    let mut merged: Vec<_> = vec![fresh_ranges[0]];
+    fn parse(input: &str) -> Result<(Vec<(u64, u64)>, Vec<u64>), ParseError> {
+        let (fresh_ranges, available) = split_once(input, "\n\n")?;
+        let available = available.lines().map(parse).collect::<Result<_, _>>()?;
+        Ok((parse_ranges(fresh_ranges)?, available))
+    }
 
-    for &(l, h) in &fresh_ranges[1..] {
-        let (_, last_h) = merged.last_mut().unwrap();
-        if l <= *last_h {
-            let new_h = h.max(*last_h);
-            *last_h = new_h;
-        } else {
-            merged.push((l, h));
-        }
+    fn part1((fresh_ranges, available): &(Vec<(u64, u64)>, Vec<u64>)) -> Result<usize, ParseError> {
+        Ok(available
+            .iter()
+            .filter(|&&x| fresh_ranges.iter().any(|&(low, high)| (low..=high).contains(&x)))
+            .count())
     }
 
-    merged
-        .into_iter()
-        .map(|(l, h)| h - l + 1)
-        .sum::<u64>()
-        .try_into()
-        .unwrap()
+    fn part2((fresh_ranges, _): &(Vec<(u64, u64)>, Vec<u64>)) -> Result<u64, ParseError> {
+        let fresh_ranges: Vec<_> = fresh_ranges.iter().copied().sorted().collect();
+
+        let Some(&first) = fresh_ranges.first() else {
+            return Ok(0);
+        };
+        let mut merged: Vec<_> = vec![first];
+
+        for &(l, h) in &fresh_ranges[1..] {
+            let (_, last_h) = merged.last_mut().unwrap();
+            if l <= *last_h {
+                let new_h = h.max(*last_h);
+                *last_h = new_h;
+            } else {
+                merged.push((l, h));
+            }
+        }
+
+        Ok(merged.into_iter().map(|(l, h)| h - l + 1).sum())
+    }
 }
 
 #[cfg(test)]
@@ -74,13 +77,13 @@ mod tests {
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 3);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 14);
     }
 }
@@ -1,138 +1,166 @@
-use itertools::Itertools;
 use ndarray::{Array2, Array3};
 use regex::Regex;
 use std::iter::{self, zip};
 
+use super::{
+    Solution,
+    parse::{ParseError, parse},
+};
+
 #[derive(Debug, Copy, Clone)]
-enum Op {
+pub enum Op {
     Add,
     Mul,
 }
 
-impl From<&str> for Op {
-    fn from(x: &str) -> Self {
-        match x {
-            "+" => Op::Add,
-            "*" => Op::Mul,
-            _ => panic!("invalid op {x}"),
-        }
-    }
-}
+impl TryFrom<&str> for Op {
+    type Error = ParseError;
 
-impl From<u8> for Op {
-    fn from(x: u8) -> Self {
+    fn try_from(x: &str) -> Result<Self, Self::Error> {
         match x {
-            b'+' => Op::Add,
-            b'*' => Op::Mul,
-            _ => panic!("invalid op {x}"),
+            "+" => Ok(Op::Add),
+            "*" => Ok(Op::Mul),
+            _ => Err(ParseError::new(x, "invalid op")),
         }
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    let ncols = input.lines().next().unwrap().split_whitespace().count();
-    let nrows = input.lines().count() - 1;
-
-    let ops: Vec<Op> = input
-        .trim_end()
+fn split_ops(input: &str) -> Result<(&str, &str), ParseError> {
+    input
+        .trim_end_matches('\n')
         .rsplit_once('\n')
-        .unwrap()
-        .1
-        .split_whitespace()
-        .map_into()
-        .collect();
-
-    let vals = Array2::from_shape_vec(
-        (nrows, ncols),
-        input
-            .lines()
-            .take(nrows)
-            .flat_map(|l| l.split_whitespace().map(|x| x.parse::<u64>().unwrap()))
-            .collect(),
-    )
-    .unwrap();
-
-    assert_eq!(ops.len(), vals.ncols());
-
-    zip(ops, vals.columns())
-        .map(|(op, vals)| -> u64 {
-            match op {
-                Op::Add => vals.into_iter().sum(),
-                Op::Mul => vals.into_iter().product(),
-            }
-        })
-        .sum::<u64>()
-        .try_into()
-        .unwrap()
+        .ok_or_else(|| ParseError::new(input, "expected rows of numbers followed by a row of ops"))
+}
+
+// The rows of numbers are kept as strings as the two parts read the digits in different directions
+pub struct Worksheet<'a> {
+    rows: Vec<&'a str>,
+    ops: Vec<(Op, u8)>,
 }
 
-pub fn part2(input: &str) -> i64 {
-    let ops_str = input.trim_end_matches('\n').rsplit_once('\n').unwrap().1;
-    let ops_bytes = ops_str.as_bytes();
-    let ops: Vec<(Op, u8)> = Regex::new(r"[*+]\s+")
-        .unwrap()
-        .find_iter(ops_str)
-        .map(|m| {
-            // Need to subtract one off all values except the last one to account for the separator
-            // between columns
-            let len = if m.end() == ops_str.len() {
-                m.len()
-            } else {
-                m.len() - 1
-            };
-            (ops_bytes[m.start()].into(), len.try_into().unwrap())
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Worksheet<'a>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Worksheet<'_>, ParseError> {
+        let (numbers, ops_str) = split_ops(input)?;
+
+        if let Some(pos) = ops_str.find(|c: char| !c.is_whitespace() && c != '+' && c != '*') {
+            return Err(ParseError::new(&ops_str[pos..], "invalid op"));
+        }
+
+        let ops: Vec<(Op, u8)> = Regex::new(r"[*+]\s*")
+            .unwrap()
+            .find_iter(ops_str)
+            .map(|m| {
+                // Need to subtract one off all values except the last one to account for the
+                // separator between columns
+                let len = if m.end() == ops_str.len() {
+                    m.len()
+                } else {
+                    m.len() - 1
+                };
+                let op = Op::try_from(&m.as_str()[..1])?;
+                let len =
+                    u8::try_from(len).map_err(|_| ParseError::new(m.as_str(), "column too wide"))?;
+                Ok((op, len))
+            })
+            .collect::<Result<_, _>>()?;
+
+        if ops.is_empty() {
+            return Err(ParseError::new(ops_str, "expected at least one op"));
+        }
+
+        Ok(Worksheet {
+            rows: numbers.lines().collect(),
+            ops,
         })
-        .collect();
-
-    // Do some array indexing black magic to get the values in the correct order. Essentially we are
-    // swapping around the order of the axes from (row, column, digit) to (column, row, digit). This
-    // means that the digits of each number are contiguous
-    let vals = {
-        let ncols = ops.len();
-        let nrows = input.trim().lines().count() - 1;
-        let max_width = ops.iter().max_by_key(|&&(_, w)| w).unwrap().1 as usize;
-
-        // Construct the vector in same order as input string
-        let mut raw = Vec::with_capacity(ncols * nrows * max_width);
-        for line in input.lines().take(nrows).map(str::as_bytes) {
-            let mut offset: usize = 0;
-            for w in ops.iter().map(|&(_, w)| w as usize) {
-                raw.extend_from_slice(&line[offset..(offset + w)]);
-                raw.extend(iter::repeat_n(b' ', max_width - w)); // Pad columns to the same width
-                offset += w + 1; // Additional increment to skip space separator
+    }
+
+    fn part1(sheet: &Worksheet) -> Result<u64, ParseError> {
+        let ncols = sheet.ops.len();
+        let nrows = sheet.rows.len();
+
+        let mut raw = Vec::with_capacity(nrows * ncols);
+        for line in &sheet.rows {
+            let old_len = raw.len();
+            for x in line.split_whitespace() {
+                raw.push(parse::<u64>(x)?);
+            }
+            if raw.len() - old_len != ncols {
+                return Err(ParseError::new(line, format!("expected {ncols} numbers")));
             }
         }
 
-        // Create array from raw vec in same order
-        let raw_array = Array3::from_shape_vec((nrows, ncols, max_width), raw).unwrap();
-
-        // Permute axes into desired order and create final array
-        Array3::from_shape_vec(
-            (ncols, max_width, nrows),
-            raw_array.permuted_axes([1, 2, 0]).iter().copied().collect(),
-        )
-        .unwrap()
-    };
-
-    zip(vals.outer_iter(), ops.iter().map(|&(op, _)| op))
-        .map(|(ns, op)| -> u64 {
-            // Filter out values which error on parsing. These values are digit-columns full of
-            // spaces where the columns have been padded out
-            let n_iter = ns.outer_iter().filter_map(|n| {
-                str::from_utf8(n.as_slice().unwrap())
-                    .unwrap()
-                    .trim()
-                    .parse::<u64>()
-                    .ok()
-            });
-            match op {
-                Op::Add => n_iter.sum(),
-                Op::Mul => n_iter.product(),
+        let vals = Array2::from_shape_vec((nrows, ncols), raw).unwrap();
+
+        Ok(zip(sheet.ops.iter().map(|&(op, _)| op), vals.columns())
+            .map(|(op, vals)| -> u64 {
+                match op {
+                    Op::Add => vals.into_iter().sum(),
+                    Op::Mul => vals.into_iter().product(),
+                }
+            })
+            .sum())
+    }
+
+    fn part2(sheet: &Worksheet) -> Result<u64, ParseError> {
+        // Do some array indexing black magic to get the values in the correct order. Essentially we
+        // are swapping around the order of the axes from (row, column, digit) to (column, row,
+        // digit). This means that the digits of each number are contiguous
+        let vals = {
+            let ncols = sheet.ops.len();
+            let nrows = sheet.rows.len();
+            let max_width = sheet.ops.iter().max_by_key(|&&(_, w)| w).unwrap().1 as usize;
+
+            // Construct the vector in same order as input string
+            let mut raw = Vec::with_capacity(ncols * nrows * max_width);
+            for &line in &sheet.rows {
+                let mut offset: usize = 0;
+                for w in sheet.ops.iter().map(|&(_, w)| w as usize) {
+                    let digits = line
+                        .as_bytes()
+                        .get(offset..(offset + w))
+                        .ok_or_else(|| ParseError::new(line, "line shorter than the row of ops"))?;
+                    raw.extend_from_slice(digits);
+                    // Pad columns to the same width
+                    raw.extend(iter::repeat_n(b' ', max_width - w));
+                    offset += w + 1; // Additional increment to skip space separator
+                }
             }
-        })
-        .sum::<u64>()
-        .try_into()
-        .unwrap()
+
+            // Create array from raw vec in same order
+            let raw_array = Array3::from_shape_vec((nrows, ncols, max_width), raw).unwrap();
+
+            // Permute axes into desired order and create final array
+            Array3::from_shape_vec(
+                (ncols, max_width, nrows),
+                raw_array.permuted_axes([1, 2, 0]).iter().copied().collect(),
+            )
+            .unwrap()
+        };
+
+        Ok(zip(vals.outer_iter(), sheet.ops.iter().map(|&(op, _)| op))
+            .map(|(ns, op)| -> u64 {
+                // Filter out values which error on parsing. These values are digit-columns full of
+                // spaces where the columns have been padded out
+                let n_iter = ns.outer_iter().filter_map(|n| {
+                    str::from_utf8(n.as_slice().unwrap())
+                        .ok()?
+                        .trim()
+                        .parse::<u64>()
+                        .ok()
+                });
+                match op {
+                    Op::Add => n_iter.sum(),
+                    Op::Mul => n_iter.product(),
+                }
+            })
+            .sum())
+    }
 }
 
 #[cfg(test)]
@@ -148,13 +176,13 @@ mod tests {
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim_start());
+        let result = Solver::solve_part1(EXAMPLE.trim_start()).unwrap();
         assert_eq!(result, 4277556);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim_start());
+        let result = Solver::solve_part2(EXAMPLE.trim_start()).unwrap();
         assert_eq!(result, 3263827);
     }
 }
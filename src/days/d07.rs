@@ -0,0 +1,114 @@
+use super::{Solution, parse::ParseError};
+
+fn parse_grid(input: &str) -> Result<(usize, Vec<&[u8]>), ParseError> {
+    let mut lines = input.lines();
+    let first = lines
+        .next()
+        .ok_or_else(|| ParseError::new(input, "empty input"))?;
+    let start = first
+        .bytes()
+        .position(|b| b == b'S')
+        .ok_or_else(|| ParseError::new(first, "no start position"))?;
+
+    let rows = lines
+        .map(|line| {
+            if line.len() == first.len() {
+                Ok(line.as_bytes())
+            } else {
+                Err(ParseError::new(
+                    line,
+                    format!("expected {} columns", first.len()),
+                ))
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((start, rows))
+}
+
+// Sweep down the manifold keeping a count of the number of timelines that have a beam in each
+// column. Returns the number of splitters that were hit along with the final counts
+fn sweep(&(start, ref rows): &(usize, Vec<&[u8]>)) -> (u64, Vec<u64>) {
+    let width = rows.first().map_or(start + 1, |r| r.len());
+
+    let mut counts = vec![0u64; width];
+    let mut next = vec![0u64; width];
+    counts[start] = 1;
+
+    let mut splits = 0;
+    for row in rows {
+        next.fill(0);
+        for (col, &count) in counts.iter().enumerate().filter(|&(_, &c)| c > 0) {
+            if row[col] == b'^' {
+                splits += 1;
+                if col > 0 {
+                    next[col - 1] += count;
+                }
+                if col + 1 < width {
+                    next[col + 1] += count;
+                }
+            } else {
+                next[col] += count;
+            }
+        }
+        std::mem::swap(&mut counts, &mut next);
+    }
+
+    (splits, counts)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = (usize, Vec<&'a [u8]>);
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<(usize, Vec<&[u8]>), ParseError> {
+        parse_grid(input)
+    }
+
+    fn part1(grid: &(usize, Vec<&[u8]>)) -> Result<u64, ParseError> {
+        Ok(sweep(grid).0)
+    }
+
+    fn part2(grid: &(usize, Vec<&[u8]>)) -> Result<u64, ParseError> {
+        Ok(sweep(grid).1.iter().sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "
+.......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+";
+
+    #[test]
+    fn example_part1() {
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, 21);
+    }
+
+    #[test]
+    fn example_part2() {
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, 40);
+    }
+}
@@ -1,151 +1,180 @@
-use std::{cmp::Reverse, collections::HashSet};
+use std::{cmp::Reverse, collections::HashMap};
 
-use ndarray::Array2;
+use itertools::iproduct;
 
-#[derive(Debug, Clone, Copy)]
+use super::{
+    Solution,
+    parse::{ParseError, parse},
+    utils::DisjointSet,
+};
+
+pub type Point = [u32; 3];
+
+// Ordered by length first. Lengths are compared squared so that they are exact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct Edge {
-    cost: f32,
+    dist2: u64,
     a: usize,
     b: usize,
 }
 
-impl Eq for Edge {}
-
-impl PartialEq for Edge {
-    fn eq(&self, other: &Self) -> bool {
-        self.a == other.a && self.b == other.b
-    }
+fn dist2(p: &Point, q: &Point) -> u64 {
+    p.iter()
+        .zip(q)
+        .map(|(&a, &b)| u64::from(a.abs_diff(b)).pow(2))
+        .sum()
 }
 
-impl Ord for Edge {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.cost.total_cmp(&other.cost)
+fn parse_boxes(input: &str) -> Result<Vec<Point>, ParseError> {
+    let boxes = input
+        .lines()
+        .map(|line| {
+            let coords = line.split(',').map(parse).collect::<Result<Vec<_>, _>>()?;
+            coords
+                .try_into()
+                .map_err(|_| ParseError::new(line, "expected three coordinates"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if boxes.len() < 2 {
+        return Err(ParseError::new(input, "expected at least two junction boxes"));
     }
+    Ok(boxes)
 }
 
-impl PartialOrd for Edge {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+// Every pair of boxes no further apart than the square root of `max_dist2`. The boxes are bucketed
+// into cells at least that wide, so only pairs in neighbouring cells need to be checked
+fn edges_within(boxes: &[Point], max_dist2: u64) -> Vec<Edge> {
+    let cell_size = max_dist2.isqrt() + 1;
+    let cell = |p: &Point| p.map(|c| u64::from(c) / cell_size);
+
+    let mut grid: HashMap<[u64; 3], Vec<usize>> = HashMap::new();
+    for (i, p) in boxes.iter().enumerate() {
+        grid.entry(cell(p)).or_default().push(i);
     }
-}
 
-fn build_edge_list(input: &str) -> (Array2<u32>, Vec<Edge>) {
-    let vals = {
-        let vec: Vec<_> = input
-            .lines()
-            .flat_map(|line| line.split(','))
-            .map(|x| x.parse::<u32>().unwrap())
-            .collect();
-        Array2::from_shape_vec((vec.len() / 3, 3), vec).unwrap()
-    };
-
-    let mut edges: Vec<Edge> = Vec::with_capacity(vals.nrows().pow(2) / 2);
-
-    for i in 0..(vals.nrows() - 1) {
-        for j in (i + 1)..(vals.nrows()) {
-            debug_assert_ne!(i, j);
-            #[expect(clippy::cast_precision_loss)]
-            let cost = (vals.row(i).mapv(|x| x as f32) - vals.row(j).mapv(|x| x as f32))
-                .pow2()
-                .sum()
-                .sqrt();
-            edges.push(Edge { cost, a: i, b: j });
+    let mut edges = Vec::new();
+    for (a, p) in boxes.iter().enumerate() {
+        let [x, y, z] = cell(p);
+        let neighbours = iproduct!(
+            x.saturating_sub(1)..=x + 1,
+            y.saturating_sub(1)..=y + 1,
+            z.saturating_sub(1)..=z + 1
+        );
+        for (x, y, z) in neighbours {
+            for &b in grid.get(&[x, y, z]).into_iter().flatten() {
+                let dist2 = dist2(p, &boxes[b]);
+                if a < b && dist2 <= max_dist2 {
+                    edges.push(Edge { dist2, a, b });
+                }
+            }
         }
     }
-    (vals, edges)
+    edges
 }
 
-fn part1_impl(input: &str, n_connections: usize) -> i64 {
-    let (_, mut all_edges) = build_edge_list(input);
-
-    let (edges_slice, _, _) = all_edges.select_nth_unstable(n_connections);
-    debug_assert_eq!(edges_slice.len(), n_connections);
-
-    edges_slice.sort_unstable();
-
-    let mut edges = edges_slice.to_vec();
-
-    let mut nodes = vec![];
-    let mut set = HashSet::new();
-    let mut connected_sets = vec![];
+// Width of the bounding box of the boxes along each axis
+fn extent(boxes: &[Point]) -> [u32; 3] {
+    [0, 1, 2].map(|axis| {
+        let (min, max) = boxes
+            .iter()
+            .map(|p| p[axis])
+            .fold((u32::MAX, 0), |(min, max), c| (min.min(c), max.max(c)));
+        max - min
+    })
+}
 
-    while let Some(edge) = edges.pop() {
-        set.clear();
-        nodes.clear();
+// Squared distance within which there would be about `n` pairs if the boxes were spread evenly
+// through their bounding box
+#[expect(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn estimate_dist2(boxes: &[Point], n: usize) -> u64 {
+    let volume: f64 = extent(boxes)
+        .iter()
+        .map(|&width| f64::from(width) + 1.0)
+        .product();
+    let pairs = (boxes.len() * (boxes.len() - 1) / 2) as f64;
+    let sphere = n as f64 / pairs * volume;
+    let radius = (sphere * 3.0 / (4.0 * std::f64::consts::PI)).cbrt();
+    (radius * radius).ceil().max(1.0) as u64
+}
 
-        nodes.push(edge.a);
-        nodes.push(edge.b);
-        while let Some(node) = nodes.pop() {
-            set.insert(node);
-            let new_node_iter = edges
-                .extract_if(.., |e| e.a == node || e.b == node)
-                .map(|e| if e.a == node { e.b } else { e.a });
-            for new_node in new_node_iter {
-                if !set.contains(&new_node) {
-                    nodes.push(new_node);
-                }
-            }
+// Calls `attempt` with every edge up to some length, doubling the length until it gives a result.
+// This avoids building the full list of edges unless the answer needs them all
+fn search_edges<R>(
+    boxes: &[Point],
+    expected_edges: usize,
+    mut attempt: impl FnMut(Vec<Edge>) -> Option<R>,
+) -> Option<R> {
+    // No pair can be further apart than opposite corners of the bounding box
+    let longest = dist2(&extent(boxes), &[0; 3]);
+    let mut max_dist2 = estimate_dist2(boxes, expected_edges);
+    loop {
+        if let Some(result) = attempt(edges_within(boxes, max_dist2.min(longest))) {
+            return Some(result);
         }
-        connected_sets.push(set.len());
+        if max_dist2 >= longest {
+            return None;
+        }
+        max_dist2 = max_dist2.saturating_mul(4);
     }
+}
 
-    let nth = connected_sets.len() - 4;
-    let (_, _, top_three) = connected_sets.select_nth_unstable(nth);
-    debug_assert_eq!(top_three.len(), 3);
+fn part1_impl(boxes: &[Point], n_connections: usize) -> Result<usize, ParseError> {
+    search_edges(boxes, n_connections, |mut edges| {
+        if edges.len() < n_connections {
+            return None;
+        }
+        edges.sort_unstable();
 
-    top_three.iter().product::<usize>().try_into().unwrap()
-}
+        let mut circuits = DisjointSet::new(boxes.len());
+        for edge in &edges[..n_connections] {
+            circuits.union(edge.a, edge.b);
+        }
 
-pub fn part1(input: &str) -> i64 {
-    part1_impl(input, 1000)
+        let mut sizes = circuits.component_sizes();
+        sizes.sort_unstable_by_key(|&size| Reverse(size));
+        Some(sizes.iter().take(3).product())
+    })
+    .ok_or_else(|| {
+        ParseError::new(
+            "",
+            format!("expected at least {n_connections} pairs of junction boxes"),
+        )
+    })
 }
 
-pub fn part2(input: &str) -> i64 {
-    let (vals, mut edges) = build_edge_list(input);
+pub struct Solver;
 
-    // Reverse order so we are popping from the end
-    edges.sort_unstable_by_key(|&x| Reverse(x));
+impl Solution for Solver {
+    type Parsed<'a> = Vec<Point>;
+    type Part1 = usize;
+    type Part2 = u64;
 
-    let mut connected_sets: Vec<Option<HashSet<usize>>> = vec![];
-
-    let mut i = 0;
-    let last_edge = loop {
-        i += 1;
-        if i % 100 == 0 {
-            connected_sets.retain(std::option::Option::is_some);
-        }
+    fn parse(input: &str) -> Result<Vec<Point>, ParseError> {
+        parse_boxes(input)
+    }
 
-        let edge = edges.pop().unwrap();
-        let mut matching_sets: Vec<_> = connected_sets
-            .iter_mut()
-            .filter(|so| {
-                so.as_ref()
-                    .is_some_and(|s| s.contains(&edge.a) || s.contains(&edge.b))
-            })
-            .collect();
-
-        match matching_sets.as_mut_slice() {
-            [] => connected_sets.push(Some(HashSet::from([edge.a, edge.b]))),
-            [Some(set)] => {
-                set.insert(edge.a);
-                set.insert(edge.b);
-            }
-            [Some(set_a), opt_set_b] => {
-                set_a.extend(opt_set_b.as_ref().unwrap());
-                **opt_set_b = None;
-            }
-            _ => panic!("invalid connected set state"),
-        }
+    fn part1(boxes: &Vec<Point>) -> Result<usize, ParseError> {
+        part1_impl(boxes, 1000)
+    }
 
-        if connected_sets
-            .iter()
-            .any(|so| so.as_ref().is_some_and(|s| s.len() >= vals.nrows()))
-        {
-            break edge;
-        }
-    };
+    // Kruskal's algorithm, stopping at the edge that joins everything into a single circuit. If the
+    // candidate edges are too short to do that then longer ones are needed
+    fn part2(boxes: &Vec<Point>) -> Result<u64, ParseError> {
+        let last_edge = search_edges(boxes, boxes.len(), |mut edges| {
+            edges.sort_unstable();
+            let mut circuits = DisjointSet::new(boxes.len());
+            edges
+                .into_iter()
+                .find(|edge| circuits.union(edge.a, edge.b) && circuits.components() == 1)
+        })
+        .expect("every pair of junction boxes has an edge");
 
-    (vals.row(last_edge.a)[0] * vals.row(last_edge.b)[0]).into()
+        Ok(u64::from(boxes[last_edge.a][0]) * u64::from(boxes[last_edge.b][0]))
+    }
 }
 
 #[cfg(test)]
@@ -177,13 +206,53 @@ mod tests {
 
     #[test]
     fn example_part1() {
-        let result = part1_impl(EXAMPLE.trim(), 10);
+        let result = part1_impl(&Solver::parse(EXAMPLE.trim()).unwrap(), 10).unwrap();
         assert_eq!(result, 40);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 25272);
     }
+
+    // Boxes spread through a large cube, like the real input
+    fn scattered_boxes(n: usize) -> Vec<Point> {
+        let mut state = 12345_u64;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            u32::try_from(state >> 33).unwrap() % 100_000
+        };
+        (0..n).map(|_| [next(), next(), next()]).collect()
+    }
+
+    #[test]
+    fn candidates_match_all_edges() {
+        let boxes = scattered_boxes(300);
+        let mut all = edges_within(&boxes, u64::MAX);
+        assert_eq!(all.len(), 300 * 299 / 2);
+        all.sort_unstable();
+
+        let max_dist2 = all[500].dist2;
+        let mut candidates = edges_within(&boxes, max_dist2);
+        candidates.sort_unstable();
+        assert_eq!(candidates, all[..candidates.len()]);
+        assert!(candidates.len() > 500);
+    }
+
+    #[test]
+    fn scattered_part1() {
+        let boxes = scattered_boxes(1000);
+        let mut all = edges_within(&boxes, u64::MAX);
+        all.sort_unstable();
+        let mut circuits = DisjointSet::new(boxes.len());
+        for edge in &all[..1000] {
+            circuits.union(edge.a, edge.b);
+        }
+        let mut sizes = circuits.component_sizes();
+        sizes.sort_unstable_by_key(|&size| Reverse(size));
+
+        let expected: usize = sizes.iter().take(3).product();
+        assert_eq!(part1_impl(&boxes, 1000).unwrap(), expected);
+    }
 }
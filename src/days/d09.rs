@@ -1,30 +1,160 @@
 use itertools::Itertools;
+use ndarray::Array2;
 
-fn parse_positions(input: &str) -> Vec<(u64, u64)> {
-    input
+use super::{
+    Solution,
+    parse::{ParseError, parse, split_once},
+};
+
+fn parse_positions(input: &str) -> Result<Vec<(u64, u64)>, ParseError> {
+    let positions: Vec<_> = input
         .lines()
         .map(|line| {
-            let (xs, ys) = line.split_once(',').unwrap();
-            (xs.parse().unwrap(), ys.parse().unwrap())
+            let (xs, ys) = split_once(line, ",")?;
+            Ok((parse(xs)?, parse(ys)?))
         })
-        .collect()
+        .collect::<Result<_, _>>()?;
+
+    if positions.is_empty() {
+        return Err(ParseError::new(input, "expected at least one red tile"));
+    }
+
+    // Consecutive tiles (wrapping around) are corners of the loop, so must share a row or column
+    let lines = input.lines().skip(1).chain(input.lines().take(1));
+    for (((x1, y1), (x2, y2)), line) in positions.iter().circular_tuple_windows().zip(lines) {
+        if x1 != x2 && y1 != y2 {
+            return Err(ParseError::new(
+                line,
+                "red tile is not in line with the previous one",
+            ));
+        }
+    }
+
+    Ok(positions)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Tile {
+    Unknown,
+    Edge,
+    Outside,
 }
 
-pub fn part1(input: &str) -> i64 {
-    let positions = parse_positions(input);
-
-    positions
-        .iter()
-        .cartesian_product(positions.iter())
-        .map(|(&(x1, y1), &(x2, y2))| (x1.abs_diff(x2) + 1) * (y1.abs_diff(y2) + 1))
-        .max()
-        .unwrap()
-        .try_into()
-        .unwrap()
+// Maps each distinct coordinate to an index in the compressed grid. Coordinates with tiles between
+// them get a gap index in between, and there is a border of padding around the outside
+struct Compressed {
+    values: Vec<u64>,
+    indices: Vec<usize>,
 }
 
-pub fn part2(_input: &str) -> i64 {
-    0
+impl Compressed {
+    fn new(vals: impl Iterator<Item = u64>) -> Self {
+        let values: Vec<_> = vals.sorted_unstable().dedup().collect();
+        let mut indices = vec![1];
+        for (a, b) in values.iter().tuple_windows() {
+            let gap = usize::from(b - a > 1);
+            indices.push(indices.last().unwrap() + 1 + gap);
+        }
+        Self { values, indices }
+    }
+
+    fn index(&self, x: u64) -> usize {
+        self.indices[self.values.binary_search(&x).unwrap()]
+    }
+
+    // Number of indices including the padding on both sides
+    fn len(&self) -> usize {
+        self.indices.last().unwrap() + 2
+    }
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<(u64, u64)>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<(u64, u64)>, ParseError> {
+        parse_positions(input)
+    }
+
+    fn part1(positions: &Vec<(u64, u64)>) -> Result<u64, ParseError> {
+        Ok(positions
+            .iter()
+            .cartesian_product(positions.iter())
+            .map(|(&(x1, y1), &(x2, y2))| (x1.abs_diff(x2) + 1) * (y1.abs_diff(y2) + 1))
+            .max()
+            .unwrap())
+    }
+
+    fn part2(positions: &Vec<(u64, u64)>) -> Result<u64, ParseError> {
+        let xs = Compressed::new(positions.iter().map(|&(x, _)| x));
+        let ys = Compressed::new(positions.iter().map(|&(_, y)| y));
+
+        let corners: Vec<_> = positions
+            .iter()
+            .map(|&(x, y)| (xs.index(x), ys.index(y)))
+            .collect();
+
+        let mut grid = Array2::from_elem((xs.len(), ys.len()), Tile::Unknown);
+
+        // Trace the loop. Consecutive corners always share either a row or a column
+        for (&(x1, y1), &(x2, y2)) in corners.iter().circular_tuple_windows() {
+            debug_assert!(x1 == x2 || y1 == y2);
+            for x in x1.min(x2)..=x1.max(x2) {
+                for y in y1.min(y2)..=y1.max(y2) {
+                    grid[(x, y)] = Tile::Edge;
+                }
+            }
+        }
+
+        // Flood fill from the padded border to find everything outside the loop
+        let mut stack = vec![(0_usize, 0_usize)];
+        grid[(0, 0)] = Tile::Outside;
+        while let Some((x, y)) = stack.pop() {
+            let neighbours = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for n in neighbours {
+                if grid.get(n) == Some(&Tile::Unknown) {
+                    grid[n] = Tile::Outside;
+                    stack.push(n);
+                }
+            }
+        }
+
+        // Summed area table of outside tiles. Offset by one so that the first row and column are
+        // zero
+        let (nx, ny) = grid.dim();
+        let mut outside = Array2::<u32>::zeros((nx + 1, ny + 1));
+        for x in 0..nx {
+            for y in 0..ny {
+                outside[(x + 1, y + 1)] = u32::from(grid[(x, y)] == Tile::Outside)
+                    + outside[(x, y + 1)]
+                    + outside[(x + 1, y)]
+                    - outside[(x, y)];
+            }
+        }
+
+        let contains_outside = |(x1, y1): (usize, usize), (x2, y2): (usize, usize)| {
+            let (xl, xh) = (x1.min(x2), x1.max(x2) + 1);
+            let (yl, yh) = (y1.min(y2), y1.max(y2) + 1);
+            outside[(xh, yh)] + outside[(xl, yl)] != outside[(xl, yh)] + outside[(xh, yl)]
+        };
+
+        Ok(positions
+            .iter()
+            .zip(&corners)
+            .tuple_combinations()
+            .filter(|&((_, &c1), (_, &c2))| !contains_outside(c1, c2))
+            .map(|((&(x1, y1), _), (&(x2, y2), _))| (x1.abs_diff(x2) + 1) * (y1.abs_diff(y2) + 1))
+            .max()
+            .unwrap_or(1))
+    }
 }
 
 #[cfg(test)]
@@ -44,13 +174,20 @@ mod tests {
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 50);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
-        assert_eq!(result, 0);
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, 24);
+    }
+
+    #[test]
+    fn adjacent_columns() {
+        // The notch between x = 5 and x = 6 has no tiles in it, so the whole box is filled
+        let input = "0,0\n5,0\n5,5\n6,5\n6,0\n10,0\n10,10\n0,10";
+        assert_eq!(Solver::solve_part2(input).unwrap(), 121);
     }
 }
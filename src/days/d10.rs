@@ -1,58 +1,184 @@
+use std::{collections::HashMap, iter::zip};
+
 use itertools::Itertools;
 
-fn parse_line(line: &str) -> (u16, Vec<u16>, Vec<u16>) {
-    let (goal_s, rest) = line.split_once("] (").unwrap();
-    let (buttons_s, costs_s) = rest.split_once(") {").unwrap();
+use super::{
+    Solution,
+    parse::{ParseError, parse, split_once},
+};
+
+// Lights and counters are stored as bits of a u16
+const MAX_LIGHTS: usize = 16;
+// Every subset of buttons is tried, so this keeps the number of subsets manageable
+const MAX_BUTTONS: usize = 16;
+
+pub struct Machine<'a> {
+    // Kept so that failures can point back at the offending machine
+    line: &'a str,
+    goal: u16,
+    buttons: Vec<u16>,
+    joltages: Vec<u16>,
+}
+
+fn parse_line(line: &str) -> Result<Machine<'_>, ParseError> {
+    let (goal_s, rest) = split_once(line, "] (")?;
+    let (buttons_s, costs_s) = split_once(rest, ") {")?;
 
+    let lights = goal_s
+        .strip_prefix('[')
+        .ok_or_else(|| ParseError::new(goal_s, "expected '['"))?;
+    if lights.len() > MAX_LIGHTS {
+        return Err(ParseError::new(lights, "too many lights"));
+    }
     let light_goal: u16 =
-        goal_s.bytes().skip(1).enumerate().fold(
+        lights.bytes().enumerate().fold(
             0u16,
             |acc, (i, b)| if b == b'#' { acc | (1 << i) } else { acc },
         );
 
-    let buttons: Vec<_> = buttons_s
-        .split(") (")
+    let buttons_list: Vec<_> = buttons_s.split(") (").collect();
+    if buttons_list.len() > MAX_BUTTONS {
+        return Err(ParseError::new(buttons_s, "too many buttons"));
+    }
+    let buttons = buttons_list
+        .into_iter()
         .map(|button| {
-            button
-                .split(',')
-                .fold(0u16, |acc, i| acc | (1 << i.parse::<u8>().unwrap()))
+            button.split(',').try_fold(0u16, |acc, i| {
+                let light = parse::<usize>(i)?;
+                if light >= lights.len() {
+                    return Err(ParseError::new(i, "no such light"));
+                }
+                Ok(acc | (1 << light))
+            })
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
 
     let costs: Vec<_> = costs_s
         .trim_end_matches('}')
         .split(',')
-        .map(|x| x.parse::<u16>().unwrap())
-        .collect();
+        .map(parse::<u16>)
+        .collect::<Result<_, _>>()?;
+    if costs.len() != lights.len() {
+        return Err(ParseError::new(costs_s, "expected one joltage per light"));
+    }
 
-    (light_goal, buttons, costs)
+    Ok(Machine {
+        line,
+        goal: light_goal,
+        buttons,
+        joltages: costs,
+    })
 }
 
-pub fn part1(input: &str) -> i64 {
-    input
-        .lines()
-        .map(|line| {
-            let (goal, buttons, _) = parse_line(line);
-            for n in 1..(buttons.len()) {
-                // TODO: combinations allocates a new Vec on every iteration. I should write my own
-                // which does not need to do this
-                if buttons
-                    .iter()
-                    .combinations(n)
-                    .any(|c| c.into_iter().copied().reduce(|acc, x| acc ^ x).unwrap() == goal)
-                {
-                    return n;
-                }
+// The effect of pressing each subset of buttons once, grouped by the parity pattern it leaves on
+// the counters
+type PressPatterns = HashMap<u16, Vec<(Vec<u16>, u32)>>;
+
+fn press_patterns(buttons: &[u16], n_counters: usize) -> PressPatterns {
+    let mut patterns = PressPatterns::new();
+    for subset in 0..(1u32 << buttons.len()) {
+        let mut effect = vec![0u16; n_counters];
+        for (_, &button) in buttons
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| subset & (1 << i) != 0)
+        {
+            for (counter, e) in effect.iter_mut().enumerate() {
+                *e += (button >> counter) & 1;
             }
-            panic!("no match found");
+        }
+        let parity = effect
+            .iter()
+            .enumerate()
+            .fold(0u16, |acc, (i, &e)| acc | ((e & 1) << i));
+        patterns
+            .entry(parity)
+            .or_default()
+            .push((effect, subset.count_ones()));
+    }
+    patterns
+}
+
+// Any solution presses some set of buttons an odd number of times, and that set must produce the
+// same parity as the target. Removing one press of each of those leaves every button pressed an
+// even number of times, which is the same as solving for half of the remaining target and
+// doubling it.
+fn min_presses(
+    target: &[u16],
+    patterns: &PressPatterns,
+    memo: &mut HashMap<Vec<u16>, Option<u32>>,
+) -> Option<u32> {
+    if target.iter().all(|&t| t == 0) {
+        return Some(0);
+    }
+    if let Some(&result) = memo.get(target) {
+        return result;
+    }
+
+    let parity = target
+        .iter()
+        .enumerate()
+        .fold(0u16, |acc, (i, &t)| acc | ((t & 1) << i));
+
+    let result = patterns
+        .get(&parity)
+        .into_iter()
+        .flatten()
+        .filter(|(effect, _)| zip(effect, target).all(|(e, t)| e <= t))
+        .filter_map(|(effect, count)| {
+            let next: Vec<_> = zip(effect, target).map(|(e, t)| (t - e) / 2).collect();
+            min_presses(&next, patterns, memo).map(|n| count + 2 * n)
         })
-        .sum::<usize>()
-        .try_into()
-        .unwrap()
+        .min();
+
+    memo.insert(target.to_vec(), result);
+    result
 }
 
-pub fn part2(_input: &str) -> i64 {
-    0
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<Machine<'a>>;
+    type Part1 = usize;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Vec<Machine<'_>>, ParseError> {
+        input.lines().map(parse_line).collect()
+    }
+
+    fn part1(machines: &Vec<Machine<'_>>) -> Result<usize, ParseError> {
+        machines
+            .iter()
+            .map(|machine| {
+                let Machine { line, goal, ref buttons, .. } = *machine;
+                for n in 1..(buttons.len()) {
+                    // TODO: combinations allocates a new Vec on every iteration. I should write my
+                    // own which does not need to do this
+                    if buttons
+                        .iter()
+                        .combinations(n)
+                        .any(|c| c.into_iter().copied().reduce(|acc, x| acc ^ x).unwrap() == goal)
+                    {
+                        return Ok(n);
+                    }
+                }
+                Err(ParseError::new(line, "no match found"))
+            })
+            .sum()
+    }
+
+    fn part2(machines: &Vec<Machine<'_>>) -> Result<u32, ParseError> {
+        let mut memo = HashMap::new();
+        machines
+            .iter()
+            .map(|machine| {
+                let patterns = press_patterns(&machine.buttons, machine.joltages.len());
+                memo.clear();
+                min_presses(&machine.joltages, &patterns, &mut memo)
+                    .ok_or_else(|| ParseError::new(machine.line, "no match found"))
+            })
+            .sum()
+    }
 }
 
 #[cfg(test)]
@@ -67,49 +193,56 @@ mod tests {
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 7);
     }
 
     #[test]
     fn p1_1() {
-        let result = part1("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}");
+        let result = Solver::solve_part1("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}").unwrap();
         assert_eq!(result, 2);
     }
 
     #[test]
     fn p1_2() {
-        let result = part1("[...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}");
+        let result = Solver::solve_part1("[...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}").unwrap();
         assert_eq!(result, 3);
     }
 
     #[test]
     fn p1_3() {
-        let result = part1("[.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}");
+        let result = Solver::solve_part1("[.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}").unwrap();
         assert_eq!(result, 2);
     }
 
-    // #[test]
-    // fn example_part2() {
-    //     let result = part2(EXAMPLE.trim());
-    //     assert_eq!(result, 33);
-    // }
-
-    // #[test]
-    // fn p2_1() {
-    //     let result = part1("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}");
-    //     assert_eq!(result, 10);
-    // }
-
-    // #[test]
-    // fn p2_2() {
-    //     let result = part1("[...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}");
-    //     assert_eq!(result, 12);
-    // }
-
-    // #[test]
-    // fn p2_3() {
-    //     let result = part1("[.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}");
-    //     assert_eq!(result, 11);
-    // }
+    #[test]
+    fn too_many_buttons() {
+        let line = |n| format!("[.#] {} {{1,2}}", vec!["(0,1)"; n].join(" "));
+        assert!(Solver::parse(&line(MAX_BUTTONS)).is_ok());
+        assert!(Solver::parse(&line(40)).is_err());
+    }
+
+    #[test]
+    fn example_part2() {
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, 33);
+    }
+
+    #[test]
+    fn p2_1() {
+        let result = Solver::solve_part2("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}").unwrap();
+        assert_eq!(result, 10);
+    }
+
+    #[test]
+    fn p2_2() {
+        let result = Solver::solve_part2("[...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}").unwrap();
+        assert_eq!(result, 12);
+    }
+
+    #[test]
+    fn p2_3() {
+        let result = Solver::solve_part2("[.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}").unwrap();
+        assert_eq!(result, 11);
+    }
 }
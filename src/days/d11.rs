@@ -1,52 +1,79 @@
-use cached::{Cached, UnboundCache, proc_macro::cached};
-use std::collections::HashMap;
-
-fn build_graph(input: &str) -> HashMap<&str, Vec<&str>> {
-    input
-        .lines()
-        .map(|line| {
-            let (current, outputs) = line.split_once(": ").unwrap();
-            (current, outputs.split_whitespace().collect())
-        })
-        .collect()
+use super::{
+    Solution,
+    parse::{ParseError, split_once},
+    utils::dag::{Dag, Interner},
+};
+
+pub struct Devices<'a> {
+    names: Interner<'a>,
+    dag: Dag,
 }
 
-#[cached(
-    ty = "UnboundCache<(String, String), u64>",
-    create = "{ UnboundCache::new() }",
-    convert = r#"{ (from.to_owned(), dest.to_owned()) }"#
-)]
-fn count_paths(graph: &HashMap<&str, Vec<&str>>, from: &str, dest: &str) -> u64 {
-    if from == dest {
-        1
-    } else {
-        graph.get(from).map_or(0, |nexts| {
-            nexts
-                .iter()
-                .map(|&next| count_paths(graph, next, dest))
-                .sum()
-        })
+impl Devices<'_> {
+    // Paths from `from` to `to` that pass through all of `via`. The graph has no cycles, so the
+    // only order the devices can be visited in is their topological order
+    fn count_paths(&self, from: &str, via: &[&str], to: &str) -> u64 {
+        let Some(mut waypoints) = via
+            .iter()
+            .map(|name| self.names.get(name))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return 0;
+        };
+        waypoints.sort_by_key(|&node| self.dag.position(node));
+
+        match (self.names.get(from), self.names.get(to)) {
+            (Some(from), Some(to)) => {
+                waypoints.insert(0, from);
+                waypoints.push(to);
+                self.dag.count_paths_through(&waypoints)
+            }
+            _ => 0,
+        }
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    COUNT_PATHS.lock().unwrap().cache_clear(); // clear cache for fair timings
+fn build_graph(input: &str) -> Result<Devices<'_>, ParseError> {
+    let mut names = Interner::default();
+    let mut successors: Vec<Vec<usize>> = Vec::new();
+    for line in input.lines() {
+        let (current, outputs) = split_once(line, ": ")?;
+        let current = names.intern(current);
+        let outputs: Vec<_> = outputs
+            .split_whitespace()
+            .map(|output| names.intern(output))
+            .collect();
+        successors.resize(names.len(), Vec::new());
+        successors[current].extend(outputs);
+    }
 
-    let graph = build_graph(input);
-    count_paths(&graph, "you", "out").try_into().unwrap()
+    let dag = Dag::new(successors).map_err(|e| {
+        ParseError::new(
+            names.name(e.cycle[0]),
+            format!("devices form a cycle: {}", e.describe(&names)),
+        )
+    })?;
+    Ok(Devices { names, dag })
 }
 
-pub fn part2(input: &str) -> i64 {
-    COUNT_PATHS.lock().unwrap().cache_clear(); // clear cache for fair timings
+pub struct Solver;
 
-    let graph = build_graph(input);
+impl Solution for Solver {
+    type Parsed<'a> = Devices<'a>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Devices<'_>, ParseError> {
+        build_graph(input)
+    }
 
-    let cp = |s, e| count_paths(&graph, s, e);
+    fn part1(devices: &Devices) -> Result<u64, ParseError> {
+        Ok(devices.count_paths("you", &[], "out"))
+    }
 
-    (cp("svr", "dac") * cp("dac", "fft") * cp("fft", "out")
-        + cp("svr", "fft") * cp("fft", "dac") * cp("dac", "out"))
-    .try_into()
-    .unwrap()
+    fn part2(devices: &Devices) -> Result<u64, ParseError> {
+        Ok(devices.count_paths("svr", &["dac", "fft"], "out"))
+    }
 }
 
 #[cfg(test)]
@@ -84,13 +111,19 @@ hhh: out
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 5);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE_2.trim());
+        let result = Solver::solve_part2(EXAMPLE_2.trim()).unwrap();
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn cycle() {
+        let err = Solver::parse("aaa: bbb\nbbb: ccc out\nccc: aaa").err().unwrap();
+        assert_eq!(err.message, "devices form a cycle: aaa -> bbb -> ccc -> aaa");
+    }
 }
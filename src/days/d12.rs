@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use super::{
+    Solution,
+    parse::{ParseError, parse, split_once},
+};
+
+// A single orientation of a shape, stored as a bitmask per row with the leftmost column in the
+// lowest bit. The anchor is the column of the first filled cell in the top row, which is the cell
+// that gets placed on the first empty cell of the region during the search
+#[derive(Debug)]
+struct Variant {
+    rows: Vec<u64>,
+    width: usize,
+    anchor: usize,
+}
+
+#[derive(Debug)]
+pub struct Shape {
+    area: usize,
+    size: usize,
+    variants: Vec<Variant>,
+}
+
+#[derive(Debug)]
+pub struct Region {
+    width: usize,
+    height: usize,
+    counts: Vec<usize>,
+}
+
+fn normalise(cells: impl Iterator<Item = (i32, i32)>) -> Vec<(i32, i32)> {
+    let cells: Vec<_> = cells.collect();
+    let min_r = cells.iter().map(|&(r, _)| r).min().unwrap();
+    let min_c = cells.iter().map(|&(_, c)| c).min().unwrap();
+    cells
+        .into_iter()
+        .map(|(r, c)| (r - min_r, c - min_c))
+        .sorted_unstable()
+        .collect()
+}
+
+fn build_variant(cells: &[(i32, i32)]) -> Variant {
+    let height = cells.iter().map(|&(r, _)| r).max().unwrap() + 1;
+    let width = cells.iter().map(|&(_, c)| c).max().unwrap() + 1;
+
+    let mut rows = vec![0u64; height.try_into().unwrap()];
+    for &(r, c) in cells {
+        rows[usize::try_from(r).unwrap()] |= 1 << c;
+    }
+
+    Variant {
+        anchor: rows[0].trailing_zeros() as usize,
+        rows,
+        width: width.try_into().unwrap(),
+    }
+}
+
+fn parse_shape(block: &str) -> Result<Shape, ParseError> {
+    let mut cells: Vec<(i32, i32)> = vec![];
+    for (r, line) in block.lines().skip(1).enumerate() {
+        for (c, b) in line.bytes().enumerate() {
+            match b {
+                b'#' => cells.push((r.try_into().unwrap(), c.try_into().unwrap())),
+                b'.' => (),
+                _ => return Err(ParseError::new(&line[c..], "invalid shape cell")),
+            }
+        }
+    }
+    if cells.is_empty() {
+        return Err(ParseError::new(block, "empty shape"));
+    }
+
+    let base = normalise(cells.iter().copied());
+    let size = base
+        .iter()
+        .map(|&(r, c)| r.max(c) + 1)
+        .max()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    // All rotations and reflections, with duplicates removed for symmetric shapes
+    let variants = (0..8)
+        .map(|transform| {
+            normalise(cells.iter().map(|&(r, c)| {
+                let (r, c) = if transform >= 4 { (r, -c) } else { (r, c) };
+                (0..(transform % 4)).fold((r, c), |(r, c), _| (c, -r))
+            }))
+        })
+        .unique()
+        .map(|cells| build_variant(&cells))
+        .collect();
+
+    Ok(Shape {
+        area: cells.len(),
+        size,
+        variants,
+    })
+}
+
+fn parse_region(line: &str, n_shapes: usize) -> Result<Region, ParseError> {
+    let (dims, counts_s) = split_once(line, ": ")?;
+    let (width, height) = split_once(dims, "x")?;
+    let region = Region {
+        width: parse(width)?,
+        height: parse(height)?,
+        counts: counts_s
+            .split_whitespace()
+            .map(parse)
+            .collect::<Result<_, _>>()?,
+    };
+
+    if region.width.min(region.height) > 64 {
+        return Err(ParseError::new(dims, "region too large"));
+    }
+    if region.counts.len() != n_shapes {
+        return Err(ParseError::new(
+            counts_s,
+            format!("expected {n_shapes} counts"),
+        ));
+    }
+    Ok(region)
+}
+
+fn parse_input(input: &str) -> Result<(Vec<Shape>, Vec<Region>), ParseError> {
+    let mut shapes = vec![];
+    let mut regions = vec![];
+    for block in input.split("\n\n") {
+        if block.lines().next().is_some_and(|l| l.ends_with(':')) {
+            shapes.push(parse_shape(block)?);
+        } else {
+            for line in block.lines() {
+                regions.push(parse_region(line, shapes.len())?);
+            }
+        }
+    }
+    if shapes.is_empty() {
+        return Err(ParseError::new(input, "expected at least one shape"));
+    }
+    Ok((shapes, regions))
+}
+
+// Exact search over the cells of the region in reading order. The first empty cell is either
+// covered by a shape anchored on it, or left empty, which uses up one cell of the available slack
+struct Packer<'a> {
+    shapes: &'a [Shape],
+    width: usize,
+    grid: Vec<u64>,
+    counts: Vec<usize>,
+    // Everything before the current cell is filled and no shape reaches further down than the
+    // largest shape size, so those rows and the remaining counts identify the state of the search
+    failed: HashSet<(usize, Vec<u64>, Vec<usize>)>,
+}
+
+impl Packer<'_> {
+    fn fits(&self, variant: &Variant, r: usize, x: usize) -> bool {
+        variant
+            .rows
+            .iter()
+            .enumerate()
+            .all(|(k, &row)| self.grid[r + k] & (row << x) == 0)
+    }
+
+    fn toggle(&mut self, variant: &Variant, r: usize, x: usize) {
+        for (k, &row) in variant.rows.iter().enumerate() {
+            self.grid[r + k] ^= row << x;
+        }
+    }
+
+    fn place(&mut self, pos: usize, slack: usize) -> bool {
+        if self.counts.iter().all(|&c| c == 0) {
+            return true;
+        }
+
+        let width = self.width;
+        let Some(pos) = (pos..(width * self.grid.len()))
+            .find(|&p| self.grid[p / width] & (1 << (p % width)) == 0)
+        else {
+            return false;
+        };
+        let (r, c) = (pos / width, pos % width);
+
+        let max_size = self.shapes.iter().map(|s| s.size).max().unwrap();
+        let key = (
+            pos,
+            self.grid[r..(r + max_size).min(self.grid.len())].to_vec(),
+            self.counts.clone(),
+        );
+        if self.failed.contains(&key) {
+            return false;
+        }
+
+        let shapes = self.shapes;
+        for (i, shape) in shapes.iter().enumerate() {
+            if self.counts[i] == 0 {
+                continue;
+            }
+            for variant in &shape.variants {
+                if variant.anchor > c
+                    || c - variant.anchor + variant.width > width
+                    || r + variant.rows.len() > self.grid.len()
+                {
+                    continue;
+                }
+                let x = c - variant.anchor;
+                if !self.fits(variant, r, x) {
+                    continue;
+                }
+
+                self.toggle(variant, r, x);
+                self.counts[i] -= 1;
+
+                if self.place(pos + 1, slack) {
+                    return true;
+                }
+
+                self.toggle(variant, r, x);
+                self.counts[i] += 1;
+            }
+        }
+
+        if slack > 0 {
+            self.grid[r] |= 1 << c;
+            if self.place(pos + 1, slack - 1) {
+                return true;
+            }
+            self.grid[r] &= !(1 << c);
+        }
+
+        self.failed.insert(key);
+        false
+    }
+}
+
+fn fits(region: &Region, shapes: &[Shape]) -> bool {
+    let available = region.width * region.height;
+    let required: usize = region
+        .counts
+        .iter()
+        .zip(shapes)
+        .map(|(&n, shape)| n * shape.area)
+        .sum();
+
+    // Not enough room even if the presents could be packed perfectly
+    if required > available {
+        return false;
+    }
+
+    // Enough room to give every present its own square box without any interlocking
+    let size = shapes.iter().map(|s| s.size).max().unwrap();
+    if (region.width / size) * (region.height / size) >= region.counts.iter().sum() {
+        return true;
+    }
+
+    // The shapes include all reflections, so the region can be transposed freely. Searching along
+    // the longer side keeps the rows narrow, which means that far more states are shared
+    let (width, height) = (
+        region.width.min(region.height),
+        region.width.max(region.height),
+    );
+    assert!(width <= 64, "region too wide for bitboard rows");
+
+    Packer {
+        shapes,
+        width,
+        grid: vec![0u64; height],
+        counts: region.counts.clone(),
+        failed: HashSet::new(),
+    }
+    .place(0, available - required)
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = (Vec<Shape>, Vec<Region>);
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<(Vec<Shape>, Vec<Region>), ParseError> {
+        parse_input(input)
+    }
+
+    fn part1((shapes, regions): &(Vec<Shape>, Vec<Region>)) -> Result<usize, ParseError> {
+        Ok(regions
+            .iter()
+            .filter(|region| fits(region, shapes))
+            .count())
+    }
+
+    fn part2(_parsed: &(Vec<Shape>, Vec<Region>)) -> Result<usize, ParseError> {
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "
+0:
+###
+##.
+##.
+
+1:
+###
+##.
+.##
+
+2:
+.##
+###
+##.
+
+3:
+##.
+###
+##.
+
+4:
+###
+#..
+###
+
+5:
+###
+.#.
+###
+
+4x4: 0 0 0 0 2 0
+12x5: 1 0 1 0 2 2
+12x5: 1 0 1 0 3 2
+";
+
+    #[test]
+    fn example_part1() {
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    fn p1_1() {
+        let (shapes, _) = parse_input(EXAMPLE.trim()).unwrap();
+        assert!(fits(&parse_region("4x4: 0 0 0 0 2 0", 6).unwrap(), &shapes));
+    }
+
+    #[test]
+    fn p1_2() {
+        let (shapes, _) = parse_input(EXAMPLE.trim()).unwrap();
+        assert!(fits(&parse_region("12x5: 1 0 1 0 2 2", 6).unwrap(), &shapes));
+    }
+
+    #[test]
+    fn p1_3() {
+        let (shapes, _) = parse_input(EXAMPLE.trim()).unwrap();
+        assert!(!fits(&parse_region("12x5: 1 0 1 0 3 2", 6).unwrap(), &shapes));
+    }
+}
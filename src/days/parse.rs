@@ -0,0 +1,88 @@
+use std::{error::Error, fmt, str::FromStr};
+
+/// An error in the puzzle input. Holds on to where the offending text came from so that it can be
+/// located within the original input when it is reported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub text: String,
+    addr: usize,
+}
+
+impl ParseError {
+    pub fn new(text: &str, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            text: text.to_string(),
+            addr: text.as_ptr().addr(),
+        }
+    }
+
+    /// One-based line and column of the offending text, if it was sliced out of `input`
+    pub fn locate(&self, input: &str) -> Option<(usize, usize)> {
+        let offset = self.addr.checked_sub(input.as_ptr().addr())?;
+        let before = input.get(..offset)?;
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        Some((line, column))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?})", self.message, self.text)
+    }
+}
+
+impl Error for ParseError {}
+
+pub fn parse<T>(text: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    text.parse()
+        .map_err(|e| ParseError::new(text, format!("could not parse: {e}")))
+}
+
+pub fn split_once<'a>(text: &'a str, delimiter: &str) -> Result<(&'a str, &'a str), ParseError> {
+    text.split_once(delimiter)
+        .ok_or_else(|| ParseError::new(text, format!("expected {delimiter:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_in_input() {
+        let input = "12,34\n56,x8\n";
+        let err = input
+            .lines()
+            .flat_map(|line| line.split(','))
+            .map(parse::<u32>)
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(err.text, "x8");
+        assert_eq!(err.locate(input), Some((2, 4)));
+    }
+
+    #[test]
+    fn locate_outside_input() {
+        let owned = String::from("abc");
+        let err = ParseError::new(&owned, "bad");
+        assert_eq!(err.locate("some other input"), None);
+    }
+
+    #[test]
+    fn missing_delimiter() {
+        let err = split_once("10 20", "-").unwrap_err();
+        assert_eq!(err.to_string(), r#"expected "-" ("10 20")"#);
+    }
+}
@@ -1,9 +1,23 @@
-pub fn part1(input: &str) -> i64 {
-    0
-}
+use super::{Solution, parse::ParseError};
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<&'a str>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<&str>, ParseError> {
+        Ok(input.lines().collect())
+    }
+
+    fn part1(_parsed: &Vec<&str>) -> Result<u64, ParseError> {
+        Ok(0)
+    }
 
-pub fn part2(input: &str) -> i64 {
-    0
+    fn part2(_parsed: &Vec<&str>) -> Result<u64, ParseError> {
+        Ok(0)
+    }
 }
 
 #[cfg(test)]
@@ -16,13 +30,13 @@ mod tests {
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 0);
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 0);
     }
 }
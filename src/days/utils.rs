@@ -1,10 +1,15 @@
 use std::{
     cmp::{Ord, Ordering, PartialOrd},
-    collections::{BinaryHeap, HashMap, HashSet},
+    collections::{BinaryHeap, HashMap, HashSet, hash_map::Entry},
     hash::Hash,
     ops::Add,
 };
 
+pub mod dag;
+mod disjoint_set;
+
+pub use disjoint_set::DisjointSet;
+
 // The std library version of this is still in unstable
 pub fn minmax_by<T, F>(a: T, b: T, mut compare: F) -> (T, T)
 where
@@ -16,26 +21,31 @@ where
     }
 }
 
-pub struct BfsWorkingSpace {
-    forward_queue: Vec<(usize, usize)>,
-    reverse_queue: Vec<(usize, usize)>,
-    visited: HashSet<(usize, usize)>,
-    next_values: Vec<(usize, usize)>,
+// Depth and parent of every node reached from one end of a search
+type Visited<T> = HashMap<T, (u32, Option<T>)>;
+
+pub struct BfsWorkingSpace<T> {
+    forward_queue: Vec<T>,
+    reverse_queue: Vec<T>,
+    forward_visited: Visited<T>,
+    reverse_visited: Visited<T>,
+    next_values: Vec<T>,
 }
 
-impl Default for BfsWorkingSpace {
+impl<T> Default for BfsWorkingSpace<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl BfsWorkingSpace {
+impl<T> BfsWorkingSpace<T> {
     #[must_use]
     pub fn new() -> Self {
         Self {
             forward_queue: Vec::new(),
             reverse_queue: Vec::new(),
-            visited: HashSet::new(),
+            forward_visited: HashMap::new(),
+            reverse_visited: HashMap::new(),
             next_values: Vec::new(),
         }
     }
@@ -43,59 +53,145 @@ impl BfsWorkingSpace {
     fn clear(&mut self) {
         self.forward_queue.clear();
         self.reverse_queue.clear();
-        self.visited.clear();
+        self.forward_visited.clear();
+        self.reverse_visited.clear();
         self.next_values.clear();
     }
 }
 
-pub fn bi_bfs<F, I>(
-    s: (usize, usize),
-    e: (usize, usize),
+/// Where the two halves of a bidirectional search joined up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meeting<T> {
+    pub distance: u32,
+    /// The first node on the path that was reached from the end rather than the start
+    pub node: T,
+    /// Every node from the start to the end, including both
+    pub path: Vec<T>,
+}
+
+// Returns the distance along with the edge (reached from start, reached from end) where the two
+// searches joined
+fn bi_bfs_search<T, F, I>(
+    s: T,
+    e: T,
     neighbours: F,
-    ws: &mut BfsWorkingSpace,
-) -> Option<u32>
+    ws: &mut BfsWorkingSpace<T>,
+) -> Option<(u32, T, T)>
 where
-    F: Fn((usize, usize)) -> I,
-    I: Iterator<Item = (usize, usize)>,
+    T: Copy + Eq + Hash,
+    F: Fn(T) -> I,
+    I: Iterator<Item = T>,
 {
-    let mut depth = 0;
-
     ws.clear();
 
     ws.forward_queue.push(s);
     ws.reverse_queue.push(e);
-    ws.visited.extend([s, e]);
+    ws.forward_visited.insert(s, (0, None));
+    ws.reverse_visited.insert(e, (0, None));
 
     loop {
-        // Get the queue with smallest number of branches
-        let (next_queue, other_queue) =
-            minmax_by(&mut ws.forward_queue, &mut ws.reverse_queue, |q1, q2| {
-                q1.len().cmp(&q2.len())
-            });
+        // Expand whichever side has the smallest number of branches
+        let forward = ws.forward_queue.len() <= ws.reverse_queue.len();
+        let (next_queue, visited, other_visited) = if forward {
+            (
+                &mut ws.forward_queue,
+                &mut ws.forward_visited,
+                &ws.reverse_visited,
+            )
+        } else {
+            (
+                &mut ws.reverse_queue,
+                &mut ws.reverse_visited,
+                &ws.forward_visited,
+            )
+        };
 
         ws.next_values.clear();
+        // The whole layer is checked, as a later meeting in it can be shorter than the first
+        let mut best: Option<(u32, T, T)> = None;
 
         while let Some(p) = next_queue.pop() {
-            let ns = neighbours(p);
-            let old_l = ws.next_values.len();
-            for n in ns {
-                if other_queue.contains(&n) {
-                    return Some(depth + 1);
-                }
-                if !ws.visited.contains(&n) {
+            let depth = visited[&p].0 + 1;
+            for n in neighbours(p) {
+                if let Some(&(other_depth, _)) = other_visited.get(&n) {
+                    if best.is_none_or(|(distance, _, _)| depth + other_depth < distance) {
+                        best = Some((depth + other_depth, p, n));
+                    }
+                } else if let Entry::Vacant(entry) = visited.entry(n) {
+                    entry.insert((depth, Some(p)));
                     ws.next_values.push(n);
                 }
             }
-            ws.visited.extend(ws.next_values[old_l..].iter());
+        }
+
+        if let Some((distance, p, n)) = best {
+            return Some(if forward {
+                (distance, p, n)
+            } else {
+                (distance, n, p)
+            });
         }
         if ws.next_values.is_empty() {
             return None;
         }
         next_queue.extend(ws.next_values.iter());
-        depth += 1;
     }
 }
 
+/// Length of the shortest path between `s` and `e`, searching from both ends at once. The
+/// neighbours of a node must be the same in both directions
+pub fn bi_bfs<T, F, I>(s: T, e: T, neighbours: F, ws: &mut BfsWorkingSpace<T>) -> Option<u32>
+where
+    T: Copy + Eq + Hash,
+    F: Fn(T) -> I,
+    I: Iterator<Item = T>,
+{
+    if s == e {
+        return Some(0);
+    }
+    bi_bfs_search(s, e, neighbours, ws).map(|(distance, _, _)| distance)
+}
+
+/// Like [`bi_bfs`], but also returns where the searches met and the path between `s` and `e`
+pub fn bi_bfs_path<T, F, I>(
+    s: T,
+    e: T,
+    neighbours: F,
+    ws: &mut BfsWorkingSpace<T>,
+) -> Option<Meeting<T>>
+where
+    T: Copy + Eq + Hash,
+    F: Fn(T) -> I,
+    I: Iterator<Item = T>,
+{
+    if s == e {
+        return Some(Meeting {
+            distance: 0,
+            node: e,
+            path: vec![e],
+        });
+    }
+    let (distance, from_start, from_end) = bi_bfs_search(s, e, neighbours, ws)?;
+
+    let chain = |mut node, visited: &Visited<T>| {
+        let mut nodes = vec![node];
+        while let Some(&(_, Some(parent))) = visited.get(&node) {
+            nodes.push(parent);
+            node = parent;
+        }
+        nodes
+    };
+    let mut path = chain(from_start, &ws.forward_visited);
+    path.reverse();
+    path.extend(chain(from_end, &ws.reverse_visited));
+
+    Some(Meeting {
+        distance,
+        node: from_end,
+        path,
+    })
+}
+
 pub trait Cost: Default + Copy + Eq + PartialEq + Ord + Add<Output = Self> {}
 
 impl<T: Default + Copy + Eq + PartialEq + Ord + Add<Output = Self>> Cost for T {}
@@ -136,6 +232,8 @@ impl<T, C: Cost> PartialOrd for WithHeuristicCost<T, C> {
 pub struct AstarWorkingSpace<T, C: Cost> {
     queue: BinaryHeap<WithHeuristicCost<T, C>>,
     visited: HashSet<T>,
+    costs: HashMap<T, C>,
+    parents: HashMap<T, T>,
 }
 
 impl<T, C: Cost> Default for AstarWorkingSpace<T, C> {
@@ -150,6 +248,8 @@ impl<T, C: Cost> AstarWorkingSpace<T, C> {
         Self {
             queue: BinaryHeap::new(),
             visited: HashSet::new(),
+            costs: HashMap::new(),
+            parents: HashMap::new(),
         }
     }
 }
@@ -205,6 +305,74 @@ where
     None
 }
 
+// Follows parent links back from `end` to the start, which is the node without a parent
+fn follow_parents<T: Copy + Eq + Hash>(end: T, parents: &HashMap<T, T>) -> Vec<T> {
+    let mut path = vec![end];
+    while let Some(&parent) = parents.get(path.last().unwrap()) {
+        path.push(parent);
+    }
+    path.reverse();
+    path
+}
+
+/// Like [`astar`], but also returns the path taken from `s` to the end node, including both. Nodes
+/// are never reopened, so the path is only a shortest one if the heuristic is consistent: it may
+/// drop by no more than the cost of each edge, as well as never overestimating
+pub fn astar_path<T, C, FE, FN, I, FH>(
+    s: T,
+    is_end: FE,
+    neighbours: FN,
+    heuristic: FH,
+    ws: &mut AstarWorkingSpace<T, C>,
+) -> Option<(C, Vec<T>)>
+where
+    T: Copy + Eq + Hash,
+    C: Cost,
+    FN: Fn(T) -> I,
+    I: Iterator<Item = (T, C)>,
+    FH: Fn(T) -> C,
+    FE: Fn(T) -> bool,
+{
+    ws.queue.clear();
+    ws.visited.clear();
+    ws.costs.clear();
+    ws.parents.clear();
+
+    ws.costs.insert(s, Default::default());
+    ws.queue.push(WithHeuristicCost {
+        node: s,
+        cost: Default::default(),
+        heuristic: heuristic(s),
+    });
+
+    while let Some(current) = ws.queue.pop() {
+        if is_end(current.node) {
+            return Some((current.cost, follow_parents(current.node, &ws.parents)));
+        }
+
+        if !ws.visited.insert(current.node) {
+            continue;
+        }
+
+        for (next, edge_weight) in neighbours(current.node) {
+            let cost = current.cost + edge_weight;
+            if ws.visited.contains(&next) || ws.costs.get(&next).is_some_and(|&c| c <= cost) {
+                continue;
+            }
+
+            ws.costs.insert(next, cost);
+            ws.parents.insert(next, current.node);
+            ws.queue.push(WithHeuristicCost {
+                node: next,
+                cost,
+                heuristic: heuristic(next),
+            });
+        }
+    }
+
+    None
+}
+
 #[derive(Clone, Copy)]
 pub struct WithCost<T, C: Cost> {
     node: T,
@@ -233,6 +401,7 @@ impl<T, C: Cost> PartialOrd for WithCost<T, C> {
 
 pub struct DijkstraWorkingSpace<T, C: Cost> {
     queue: BinaryHeap<WithCost<T, C>>,
+    settled: HashSet<T>,
 }
 
 impl<T, C: Cost> Default for DijkstraWorkingSpace<T, C> {
@@ -246,6 +415,7 @@ impl<T, C: Cost> DijkstraWorkingSpace<T, C> {
     pub fn new() -> Self {
         Self {
             queue: BinaryHeap::new(),
+            settled: HashSet::new(),
         }
     }
 }
@@ -272,6 +442,14 @@ where
     let mut cost_map = HashMap::with_capacity(map_size);
 
     while let Some(current) = ws.queue.pop() {
+        // A cheaper route to this node has been found since this entry was pushed
+        if cost_map
+            .get(&current.node)
+            .is_some_and(|&c| c < current.cost)
+        {
+            continue;
+        }
+
         for (next, edge_weight) in neighbours(current.node) {
             let cost = current.cost + edge_weight;
 
@@ -287,3 +465,204 @@ where
 
     cost_map
 }
+
+/// The result of [`dijkstra`]: the cost of every node settled, and every predecessor that a
+/// shortest path to each node can come from
+pub struct ShortestPaths<T, C> {
+    costs: HashMap<T, C>,
+    predecessors: HashMap<T, Vec<T>>,
+    target: Option<T>,
+}
+
+impl<T: Copy + Eq + Hash, C: Cost> ShortestPaths<T, C> {
+    /// The first node found that matched the target predicate
+    pub fn target(&self) -> Option<T> {
+        self.target
+    }
+
+    pub fn cost(&self, node: T) -> Option<C> {
+        self.costs.get(&node).copied()
+    }
+
+    /// One of the shortest paths to `node`, starting with the start node
+    pub fn path_to(&self, node: T) -> Option<Vec<T>> {
+        self.costs.get(&node)?;
+        let mut path = vec![node];
+        while let Some(&prev) = self
+            .predecessors
+            .get(path.last().unwrap())
+            .and_then(|p| p.first())
+        {
+            path.push(prev);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every shortest path to `node`. There can be exponentially many of these
+    pub fn all_paths_to(&self, node: T) -> Vec<Vec<T>> {
+        if !self.costs.contains_key(&node) {
+            return Vec::new();
+        }
+        let Some(predecessors) = self.predecessors.get(&node) else {
+            return vec![vec![node]];
+        };
+        predecessors
+            .iter()
+            .flat_map(|&prev| self.all_paths_to(prev))
+            .map(|mut path| {
+                path.push(node);
+                path
+            })
+            .collect()
+    }
+}
+
+/// Dijkstra's algorithm from `s`, stopping once a node matching `is_target` is settled. Ties are
+/// kept, so that all shortest paths can be recovered
+pub fn dijkstra<T, C, FT, FN, I>(
+    s: T,
+    is_target: FT,
+    neighbours: FN,
+    ws: &mut DijkstraWorkingSpace<T, C>,
+) -> ShortestPaths<T, C>
+where
+    T: Copy + Eq + Hash,
+    C: Cost,
+    FT: Fn(T) -> bool,
+    FN: Fn(T) -> I,
+    I: Iterator<Item = (T, C)>,
+{
+    ws.queue.clear();
+    ws.settled.clear();
+
+    let mut paths = ShortestPaths {
+        costs: HashMap::from([(s, C::default())]),
+        predecessors: HashMap::new(),
+        target: None,
+    };
+    ws.queue.push(WithCost {
+        node: s,
+        cost: Default::default(),
+    });
+
+    while let Some(current) = ws.queue.pop() {
+        if !ws.settled.insert(current.node) {
+            continue;
+        }
+        if is_target(current.node) {
+            paths.target = Some(current.node);
+            break;
+        }
+
+        for (next, edge_weight) in neighbours(current.node) {
+            if ws.settled.contains(&next) {
+                continue;
+            }
+            let cost = current.cost + edge_weight;
+
+            match paths.costs.get(&next) {
+                Some(&c) if c < cost => (),
+                Some(&c) if c == cost => {
+                    paths
+                        .predecessors
+                        .entry(next)
+                        .or_default()
+                        .push(current.node);
+                }
+                _ => {
+                    paths.costs.insert(next, cost);
+                    paths.predecessors.insert(next, vec![current.node]);
+                    ws.queue.push(WithCost { node: next, cost });
+                }
+            }
+        }
+    }
+
+    // Costs of nodes that were never settled may not be the lowest
+    paths.costs.retain(|node, _| ws.settled.contains(node));
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Four connected neighbours on a 5x5 grid with a wall down x = 2, except at y = 4
+    fn grid_neighbours((x, y): (i32, i32)) -> impl Iterator<Item = (i32, i32)> {
+        [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+            .into_iter()
+            .filter(|&(x, y)| (0..5).contains(&x) && (0..5).contains(&y) && (x != 2 || y == 4))
+    }
+
+    #[test]
+    fn bi_bfs_distance() {
+        let mut ws = BfsWorkingSpace::new();
+        assert_eq!(bi_bfs((0, 0), (4, 0), grid_neighbours, &mut ws), Some(12));
+        assert_eq!(bi_bfs((0, 0), (1, 1), grid_neighbours, &mut ws), Some(2));
+        assert_eq!(bi_bfs((0, 0), (0, 0), grid_neighbours, &mut ws), Some(0));
+        assert_eq!(bi_bfs((0, 0), (9, 9), grid_neighbours, &mut ws), None);
+    }
+
+    #[test]
+    fn bi_bfs_meeting() {
+        let mut ws = BfsWorkingSpace::new();
+        let meeting = bi_bfs_path((0, 0), (4, 0), grid_neighbours, &mut ws).unwrap();
+        assert_eq!(meeting.distance, 12);
+        assert_eq!(meeting.path.len(), 13);
+        assert_eq!(meeting.path.first(), Some(&(0, 0)));
+        assert_eq!(meeting.path.last(), Some(&(4, 0)));
+        assert!(meeting.path.contains(&(2, 4)));
+        assert!(meeting.path.contains(&meeting.node));
+        assert!(
+            meeting
+                .path
+                .windows(2)
+                .all(|w| grid_neighbours(w[0]).any(|n| n == w[1]))
+        );
+    }
+
+    // 0 -> 1 -> 3 and 0 -> 2 -> 3 both cost 2, 0 -> 3 directly costs 5
+    fn neighbours(node: u32) -> impl Iterator<Item = (u32, u32)> {
+        let edges: &[(u32, u32)] = match node {
+            0 => &[(1, 1), (2, 1), (3, 5)],
+            1 | 2 => &[(3, 1)],
+            3 => &[(4, 1)],
+            _ => &[],
+        };
+        edges.iter().copied()
+    }
+
+    #[test]
+    fn dijkstra_paths() {
+        let mut ws = DijkstraWorkingSpace::new();
+        let paths = dijkstra(0, |n| n == 3, neighbours, &mut ws);
+        assert_eq!(paths.target(), Some(3));
+        assert_eq!(paths.cost(3), Some(2));
+        // Stopped before reaching 4
+        assert_eq!(paths.cost(4), None);
+        assert_eq!(paths.path_to(3), Some(vec![0, 1, 3]));
+
+        let mut all = paths.all_paths_to(3);
+        all.sort();
+        assert_eq!(all, [vec![0, 1, 3], vec![0, 2, 3]]);
+    }
+
+    #[test]
+    fn dijkstra_no_target() {
+        let mut ws = DijkstraWorkingSpace::new();
+        let paths = dijkstra(0, |_| false, neighbours, &mut ws);
+        assert_eq!(paths.target(), None);
+        assert_eq!(paths.cost(4), Some(3));
+    }
+
+    #[test]
+    fn astar_with_path() {
+        let mut ws = AstarWorkingSpace::new();
+        let result = astar_path(0, |n| n == 4, neighbours, |n| u32::from(n < 4), &mut ws);
+        let (cost, path) = result.unwrap();
+        assert_eq!(cost, 3);
+        assert_eq!(path.len(), 4);
+        assert_eq!((path[0], path[2], path[3]), (0, 3, 4));
+    }
+}
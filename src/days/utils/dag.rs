@@ -0,0 +1,217 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+/// Gives each distinct name a small integer id, in the order they are first seen
+#[derive(Debug, Default)]
+pub struct Interner<'a> {
+    ids: HashMap<&'a str, usize>,
+    names: Vec<&'a str>,
+}
+
+impl<'a> Interner<'a> {
+    pub fn intern(&mut self, name: &'a str) -> usize {
+        *self.ids.entry(name).or_insert_with(|| {
+            self.names.push(name);
+            self.names.len() - 1
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &'a str {
+        self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// The nodes of a cycle in the order they are visited, without repeating the first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    pub cycle: Vec<usize>,
+}
+
+impl CycleError {
+    pub fn describe(&self, names: &Interner) -> String {
+        let mut names: Vec<_> = self.cycle.iter().map(|&node| names.name(node)).collect();
+        names.push(names[0]);
+        names.join(" -> ")
+    }
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut nodes: Vec<_> = self.cycle.iter().map(ToString::to_string).collect();
+        nodes.push(nodes[0].clone());
+        write!(f, "cycle {}", nodes.join(" -> "))
+    }
+}
+
+impl Error for CycleError {}
+
+/// Orders the nodes so that every edge goes from an earlier node to a later one. `successors[n]`
+/// holds the nodes that `n` has edges to
+pub fn topological_sort(successors: &[Vec<usize>]) -> Result<Vec<usize>, CycleError> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        New,
+        OnStack,
+        Done,
+    }
+
+    let mut state = vec![State::New; successors.len()];
+    let mut order = Vec::with_capacity(successors.len());
+    // Depth first search, where each entry is a node and how many of its successors have been seen
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+
+    for root in 0..successors.len() {
+        if state[root] != State::New {
+            continue;
+        }
+        state[root] = State::OnStack;
+        stack.push((root, 0));
+
+        while let Some((node, seen)) = stack.last_mut() {
+            let node = *node;
+            let Some(&next) = successors[node].get(*seen) else {
+                state[node] = State::Done;
+                order.push(node);
+                stack.pop();
+                continue;
+            };
+            *seen += 1;
+            match state[next] {
+                State::New => {
+                    state[next] = State::OnStack;
+                    stack.push((next, 0));
+                }
+                State::OnStack => {
+                    let start = stack.iter().position(|&(n, _)| n == next).unwrap();
+                    let cycle = stack[start..].iter().map(|&(n, _)| n).collect();
+                    return Err(CycleError { cycle });
+                }
+                State::Done => (),
+            }
+        }
+    }
+
+    // Nodes finish after everything reachable from them
+    order.reverse();
+    Ok(order)
+}
+
+/// A directed acyclic graph over nodes `0..n`
+#[derive(Debug, Clone)]
+pub struct Dag {
+    successors: Vec<Vec<usize>>,
+    order: Vec<usize>,
+    position: Vec<usize>,
+}
+
+impl Dag {
+    pub fn new(successors: Vec<Vec<usize>>) -> Result<Self, CycleError> {
+        let order = topological_sort(&successors)?;
+        let mut position = vec![0; order.len()];
+        for (i, &node) in order.iter().enumerate() {
+            position[node] = i;
+        }
+        Ok(Self {
+            successors,
+            order,
+            position,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.successors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.successors.is_empty()
+    }
+
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Where `node` comes in the topological order
+    pub fn position(&self, node: usize) -> usize {
+        self.position[node]
+    }
+
+    /// Number of distinct paths from `source` to every node. Only nodes after `source` in the
+    /// topological order can be reached, so the rest are skipped
+    pub fn path_counts_from(&self, source: usize) -> Vec<u64> {
+        let mut counts = vec![0; self.len()];
+        counts[source] = 1;
+        for &node in &self.order[self.position[source]..] {
+            let count = counts[node];
+            if count == 0 {
+                continue;
+            }
+            for &next in &self.successors[node] {
+                counts[next] += count;
+            }
+        }
+        counts
+    }
+
+    pub fn count_paths(&self, from: usize, to: usize) -> u64 {
+        self.path_counts_from(from)[to]
+    }
+
+    /// Number of paths that visit each of `waypoints` in the order given, starting at the first and
+    /// ending at the last
+    pub fn count_paths_through(&self, waypoints: &[usize]) -> u64 {
+        waypoints
+            .windows(2)
+            .map(|pair| self.count_paths(pair[0], pair[1]))
+            .product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 3, 0 -> 2 -> 3, 1 -> 2
+    fn diamond() -> Dag {
+        Dag::new(vec![vec![1, 2], vec![3, 2], vec![3], vec![]]).unwrap()
+    }
+
+    #[test]
+    fn order() {
+        let dag = diamond();
+        assert_eq!(dag.order(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn path_counts() {
+        let dag = diamond();
+        assert_eq!(dag.path_counts_from(0), [1, 1, 2, 3]);
+        assert_eq!(dag.path_counts_from(2), [0, 0, 1, 1]);
+        assert_eq!(dag.count_paths_through(&[0, 2, 3]), 2);
+        assert_eq!(dag.count_paths_through(&[0, 1, 2, 3]), 1);
+        assert_eq!(dag.count_paths_through(&[0, 2, 1, 3]), 0);
+    }
+
+    #[test]
+    fn cycle() {
+        let mut names = Interner::default();
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| names.intern(name));
+        let mut successors = vec![vec![]; names.len()];
+        successors[a].push(b);
+        successors[b].push(c);
+        successors[c].extend([d, a]);
+
+        let err = Dag::new(successors).unwrap_err();
+        assert_eq!(err.describe(&names), "a -> b -> c -> a");
+    }
+}
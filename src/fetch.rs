@@ -0,0 +1,256 @@
+use std::{
+    env,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2025";
+pub const TOKEN_VAR: &str = "AOC_TOKEN";
+pub const BASE_URL_VAR: &str = "AOC_BASE_URL";
+pub const CONTACT_VAR: &str = "AOC_CONTACT";
+
+// The site asks that automated requests say where they come from and who to contact about them
+fn user_agent(contact: Option<&str>) -> String {
+    let repository = env!("CARGO_PKG_REPOSITORY");
+    let details: Vec<_> = ["input fetcher, only downloads missing inputs", repository]
+        .into_iter()
+        .chain(contact)
+        .filter(|detail| !detail.is_empty())
+        .collect();
+    format!(
+        "{}/{} ({})",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        details.join("; ")
+    )
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    NoToken,
+    Request(String),
+    Status(u16, String),
+    Body(&'static str),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::NoToken => write!(f, "{TOKEN_VAR} is not set"),
+            FetchError::Request(e) => write!(f, "request failed: {e}"),
+            FetchError::Status(status, body) => {
+                let message = body.lines().next().unwrap_or_default();
+                write!(f, "server returned {status}: {message}")
+            }
+            FetchError::Body(reason) => write!(f, "unexpected response: {reason}"),
+            FetchError::Io(path, e) => write!(f, "could not write {}: {e}", path.display()),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    Downloaded,
+    AlreadyPresent,
+}
+
+pub struct Fetcher {
+    agent: ureq::Agent,
+    base_url: String,
+    token: String,
+}
+
+impl Fetcher {
+    /// `contact` is added to the User-Agent, so that the site can reach whoever runs the fetcher
+    pub fn new(base_url: &str, token: &str, contact: Option<&str>) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(&user_agent(contact))
+            .timeout(Duration::from_secs(30))
+            .build();
+        Self {
+            agent,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    /// Takes the session token from `AOC_TOKEN` and a contact such as an email address from
+    /// `AOC_CONTACT`. `AOC_BASE_URL` can point at a different server
+    pub fn from_env() -> Result<Self, FetchError> {
+        let token = env::var(TOKEN_VAR).map_err(|_| FetchError::NoToken)?;
+        let base_url = env::var(BASE_URL_VAR).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        let contact = env::var(CONTACT_VAR).ok();
+        Ok(Self::new(&base_url, &token, contact.as_deref()))
+    }
+
+    pub fn fetch(&self, day: u32) -> Result<String, FetchError> {
+        let url = format!("{}/day/{day}/input", self.base_url);
+        let response = match self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.token))
+            .call()
+        {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(FetchError::Request(e.to_string())),
+        };
+        let status = response.status();
+        let body = response
+            .into_string()
+            .map_err(|e| FetchError::Request(e.to_string()))?;
+
+        if status != 200 {
+            return Err(FetchError::Status(status, body));
+        }
+        validate(&body)?;
+        Ok(body)
+    }
+
+    /// Downloads the input for `day` to `path`, unless it is already there
+    pub fn fetch_missing(&self, day: u32, path: &Path) -> Result<Fetched, FetchError> {
+        if path.exists() {
+            return Ok(Fetched::AlreadyPresent);
+        }
+        let input = self.fetch(day)?;
+        write_atomic(path, &input).map_err(|e| FetchError::Io(path.to_path_buf(), e))?;
+        Ok(Fetched::Downloaded)
+    }
+}
+
+// An expired token gets a login page rather than an error status, which must not end up in data/
+fn validate(body: &str) -> Result<(), FetchError> {
+    if body.trim().is_empty() {
+        Err(FetchError::Body("empty input"))
+    } else if body.trim_start().starts_with('<') {
+        Err(FetchError::Body(
+            "got HTML instead of an input, the token may have expired",
+        ))
+    } else if !body.ends_with('\n') {
+        Err(FetchError::Body("input is truncated"))
+    } else {
+        Ok(())
+    }
+}
+
+// Writes to a temporary file next to `path` then renames it, so an interrupted download never
+// leaves a partial input behind
+fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    // Serves one canned response per connection, returning the request lines it received
+    fn stub_server(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut headers = Vec::new();
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap();
+                    if line.is_empty() {
+                        break;
+                    }
+                    headers.push(line);
+                }
+                requests.push(headers.join("\n"));
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc25-fetch-{}", std::process::id()));
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn fetches_input() {
+        let (url, server) = stub_server(vec![(200, "1\n2\n3\n")]);
+        let path = temp_path("d03.txt");
+        let fetcher = Fetcher::new(&url, "abc", Some("me@example.com"));
+        assert_eq!(
+            fetcher.fetch_missing(3, &path).unwrap(),
+            Fetched::Downloaded
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1\n2\n3\n");
+
+        let request = &server.join().unwrap()[0];
+        assert!(request.starts_with("GET /day/3/input "));
+        assert!(request.contains("Cookie: session=abc"));
+        let header = format!("User-Agent: {}", user_agent(Some("me@example.com")));
+        assert!(request.contains(&header));
+        assert!(
+            header.ends_with("; https://github.com/TimQuelch/advent-of-code-2025; me@example.com)")
+        );
+        assert!(user_agent(None).ends_with("; https://github.com/TimQuelch/advent-of-code-2025)"));
+    }
+
+    #[test]
+    fn skips_present_input() {
+        let path = temp_path("d04.txt");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "existing\n").unwrap();
+        // Nothing is listening here, so any request would fail
+        let fetcher = Fetcher::new("http://127.0.0.1:9", "abc", None);
+        assert_eq!(
+            fetcher.fetch_missing(4, &path).unwrap(),
+            Fetched::AlreadyPresent
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "existing\n");
+    }
+
+    #[test]
+    fn rejects_bad_responses() {
+        let (url, server) = stub_server(vec![
+            (
+                400,
+                "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n",
+            ),
+            (200, "<!DOCTYPE html>\n<html></html>\n"),
+            (200, ""),
+        ]);
+        let path = temp_path("d05.txt");
+        let fetcher = Fetcher::new(&url, "abc", None);
+        for _ in 0..3 {
+            assert!(fetcher.fetch_missing(5, &path).is_err());
+            assert!(!path.exists());
+        }
+        server.join().unwrap();
+    }
+}
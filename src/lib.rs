@@ -1 +1,4 @@
+pub mod answers;
 pub mod days;
+pub mod fetch;
+pub mod scaffold;
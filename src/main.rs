@@ -1,6 +1,50 @@
-use std::time::{Duration, Instant};
+use std::{
+    fmt, fs,
+    io::{self, Read, Write},
+    iter,
+    ops::RangeInclusive,
+    path::Path,
+    process::ExitCode,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
 
-use aoc25::days::{DAYS, Day};
+use aoc25::{
+    answers::{ANSWERS_FILE, Answers, Verdict},
+    days::{
+        Answer, DAYS, Day, SolveError, Stage,
+        crypt::{self, Key},
+    },
+    fetch::{Fetched, Fetcher},
+    scaffold,
+};
+
+// This year's event only has 12 days, but any day an event can have is accepted
+const PUZZLE_DAYS: RangeInclusive<u32> = 1..=12;
+const MAX_DAY: u32 = 25;
+
+const USAGE: &str = "\
+usage: aoc25 [OPTIONS] [DAYS...]
+       aoc25 verify [DAYS...]
+       aoc25 fetch [DAYS...]
+       aoc25 encrypt|decrypt INPUT OUTPUT
+       aoc25 new DAY [--example PATH]
+       aoc25 --list [DAYS...]
+
+Days are numbers or names like 7, 07 or d07, or ranges like 1-5. A part can be picked with
+a suffix, so 7:2 only solves part 2 of day 7. With no days every day is solved.
+
+options:
+  --repeat N          time each step over at least N samples
+  --min-time SECONDS  keep sampling until this much time has been spent
+  --format FORMAT     text, json, csv or markdown
+  --jobs N            solve N days at once
+  --input PATH        solve a single day against PATH instead of its own input, - for stdin
+  --list              show the days and whether their inputs are present
+  --example PATH      with new, fill in the example test from PATH, - for stdin
+  --help              show this message";
 
 #[expect(clippy::cast_precision_loss)]
 fn format_duration(d: Duration) -> String {
@@ -27,39 +71,1019 @@ where
     (result, duration)
 }
 
-fn run_days(days: Vec<&Day>) {
-    let (results, total) = time_execution(|| {
-        days.into_iter()
-            .map(|day| {
-                let (part1, duration1) = time_execution(|| day.part1());
-                let (part2, duration2) = time_execution(|| day.part2());
-                (day.name.as_str(), part1, part2, duration1, duration2)
+/// How the runner prints its results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "markdown" => Ok(Format::Markdown),
+            _ => Err(format!(
+                "invalid --format {s:?}, expected text, json, csv or markdown"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Options {
+    sampling: Sampling,
+    format: Format,
+    jobs: usize,
+    // Solves a single day against this file instead of its own input, "-" for stdin
+    input: Option<String>,
+}
+
+impl Options {
+    // Takes `--repeat N`, `--min-time SECONDS`, `--format FORMAT`, `--jobs N` and `--input PATH`
+    // out of the arguments. A lone "-" is short for `--input -`
+    fn from_args(args: &mut Vec<String>) -> Result<Self, String> {
+        let mut options = Options {
+            sampling: Sampling::default(),
+            format: Format::Text,
+            jobs: 1,
+            input: None,
+        };
+        while let Some(i) = args.iter().position(|arg| arg.starts_with("--")) {
+            let option = args[i].as_str();
+            if !["--repeat", "--min-time", "--format", "--jobs", "--input"].contains(&option) {
+                return Err(format!("unknown option {option}"));
+            }
+            let value = args
+                .get(i + 1)
+                .ok_or_else(|| format!("{option} needs a value"))?;
+            match option {
+                "--repeat" => {
+                    options.sampling.repeat = value
+                        .parse()
+                        .map_err(|e| format!("invalid --repeat {value:?}: {e}"))?;
+                }
+                "--min-time" => {
+                    options.sampling.min_time = value
+                        .parse()
+                        .ok()
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                        .ok_or_else(|| format!("invalid --min-time {value:?}, expected seconds"))?;
+                }
+                "--format" => options.format = value.parse()?,
+                "--jobs" => {
+                    options.jobs =
+                        value.parse().ok().filter(|&jobs| jobs > 0).ok_or_else(|| {
+                            format!("invalid --jobs {value:?}, expected at least 1")
+                        })?;
+                }
+                "--input" => options.input = Some(value.clone()),
+                _ => unreachable!(),
+            }
+            args.drain(i..=i + 1);
+        }
+        // Only after the options, so that a "-" given to `--input` is taken as its value
+        if let Some(i) = args.iter().position(|arg| arg == "-") {
+            args.remove(i);
+            options.input = Some("-".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// How many times to run each step when timing it. The default runs everything once
+#[derive(Debug, Clone, Copy)]
+struct Sampling {
+    repeat: usize,
+    min_time: Duration,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Self {
+            repeat: 1,
+            min_time: Duration::ZERO,
+        }
+    }
+}
+
+impl Sampling {
+    fn is_single(&self) -> bool {
+        self.repeat <= 1 && self.min_time.is_zero()
+    }
+
+    // Warms up, then runs `f` until there are enough samples and enough time has been spent. The
+    // first result is returned along with statistics over the samples, whose total also counts the
+    // first run and the warmup
+    fn run<F, T>(&self, f: F) -> (T, Stats)
+    where
+        F: Fn() -> T,
+    {
+        let (result, duration) = time_execution(&f);
+        if self.is_single() {
+            return (result, Stats::new(vec![duration]));
+        }
+
+        let mut unsampled = duration;
+        for _ in 0..self.repeat.div_ceil(10) {
+            unsampled += time_execution(&f).1;
+        }
+        let mut samples = Vec::with_capacity(self.repeat);
+        let mut total = Duration::ZERO;
+        while samples.len() < self.repeat || total < self.min_time {
+            let (_, duration) = time_execution(&f);
+            samples.push(duration);
+            total += duration;
+        }
+        let mut stats = Stats::new(samples);
+        stats.total += unsampled;
+        (result, stats)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Stats {
+    min: Duration,
+    median: Duration,
+    mean: Duration,
+    stddev: Duration,
+    samples: usize,
+    // Time spent in every run, including any that were not sampled
+    total: Duration,
+}
+
+impl Stats {
+    #[expect(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn new(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        let n = samples.len();
+        let median = if n.is_multiple_of(2) {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        } else {
+            samples[n / 2]
+        };
+        let total = samples.iter().sum::<Duration>();
+        let mean = total / n as u32;
+        let variance = samples
+            .iter()
+            .map(|d| (d.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / n as f64;
+        Self {
+            min: samples[0],
+            median,
+            mean,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+            samples: n,
+            total,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {}, median {}, mean {}, stddev {} ({} samples)",
+            format_duration(self.min),
+            format_duration(self.median),
+            format_duration(self.mean),
+            format_duration(self.stddev),
+            self.samples
+        )
+    }
+}
+
+fn format_answer(answer: &Result<Answer, SolveError>) -> String {
+    match answer {
+        Ok(answer) => answer.to_string(),
+        Err(_) => "error".to_string(),
+    }
+}
+
+/// Which parts of a day to solve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Parts([bool; 2]);
+
+impl Parts {
+    const NONE: Parts = Parts([false, false]);
+    const BOTH: Parts = Parts([true, true]);
+
+    fn only(part: u8) -> Self {
+        Parts([part == 1, part == 2])
+    }
+
+    fn union(self, other: Parts) -> Self {
+        Parts([self.0[0] || other.0[0], self.0[1] || other.0[1]])
+    }
+}
+
+/// A day picked on the command line, along with the parts to solve
+#[derive(Clone, Copy)]
+struct Selected<'a> {
+    day: &'a Day,
+    parts: Parts,
+}
+
+type PartResult = (Result<Answer, SolveError>, Stats);
+
+// Parses the input once then solves the selected parts from it, timing each step. Parts that were
+// not selected are `None`
+fn solve_day(
+    selected: Selected,
+    sampling: Sampling,
+) -> (Stats, Result<[Option<PartResult>; 2], SolveError>) {
+    let Selected { day, parts } = selected;
+    // Read the input up front so that it does not count towards the parse time
+    if let Err(err) = day.input() {
+        return (Stats::new(vec![Duration::ZERO]), Err(err));
+    }
+    let (parsed, parse_stats) = sampling.run(|| day.parse());
+    let parts = parsed.map(|parsed| {
+        [
+            parts.0[0].then(|| sampling.run(|| parsed.part1())),
+            parts.0[1].then(|| sampling.run(|| parsed.part2())),
+        ]
+    });
+    (parse_stats, parts)
+}
+
+type DayResult<'a> = (
+    &'a str,
+    (Stats, Result<[Option<PartResult>; 2], SolveError>),
+);
+
+// Solves the days on `jobs` threads, each taking the next unsolved day until none are left.
+// Results are in the same order as `days`
+fn solve_days<'a>(days: &[Selected<'a>], sampling: Sampling, jobs: usize) -> Vec<DayResult<'a>> {
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<_> = thread::scope(|s| {
+        let workers: Vec<_> = (0..jobs.min(days.len()))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(&selected) = days.get(index) else {
+                            break;
+                        };
+                        let result = solve_day(selected, sampling);
+                        results.push((index, (selected.day.name.as_str(), result)));
+                    }
+                    results
+                })
             })
-            .collect::<Vec<_>>()
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
     });
-    for (name, part1, part2, duration1, duration2) in results {
-        println!(
-            "{}: {}, {} ({}, {})",
-            name,
-            part1,
-            part2,
-            format_duration(duration1),
-            format_duration(duration2)
-        );
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+fn run_days(days: &[Selected], options: &Options) {
+    let sampling = options.sampling;
+    let (results, total) = time_execution(|| solve_days(days, sampling, options.jobs));
+    // With several jobs the time spent solving can be more than the time that passed
+    let cpu_time: Duration = results
+        .iter()
+        .flat_map(|(_, (parse_stats, parts))| {
+            let parts = parts
+                .iter()
+                .flatten()
+                .flatten()
+                .map(|(_, stats)| stats.total);
+            iter::once(parse_stats.total).chain(parts)
+        })
+        .sum();
+
+    if options.format != Format::Text {
+        let records: Vec<_> = results.iter().map(Record::new).collect();
+        match options.format {
+            Format::Json => print!("{}", format_json(&records)),
+            Format::Csv => print!("{}", format_csv(&records)),
+            Format::Markdown => print!("{}", format_markdown(&records)),
+            Format::Text => unreachable!(),
+        }
+        for (_, (_, parts)) in results {
+            let errors = match parts {
+                Ok(parts) => parts
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(part, _)| part.err())
+                    .collect(),
+                Err(err) => vec![err],
+            };
+            for err in errors {
+                eprintln!("{err}");
+            }
+        }
+        return;
+    }
+
+    for (name, (parse_stats, parts)) in results {
+        match parts {
+            Ok(parts) => {
+                // Parts that were not selected show as "-"
+                let [answer1, answer2] = parts.each_ref().map(|part| {
+                    part.as_ref()
+                        .map_or_else(|| "-".to_string(), |(answer, _)| format_answer(answer))
+                });
+                if sampling.is_single() {
+                    let [time1, time2] = parts.each_ref().map(|part| {
+                        part.as_ref().map_or_else(
+                            || "-".to_string(),
+                            |(_, stats)| format_duration(stats.min),
+                        )
+                    });
+                    println!(
+                        "{name}: {answer1}, {answer2} (parse {}, {time1}, {time2})",
+                        format_duration(parse_stats.min)
+                    );
+                } else {
+                    println!("{name}: {answer1}, {answer2}");
+                    println!("  parse:  {parse_stats}");
+                    for (part, result) in (1..).zip(&parts) {
+                        if let Some((_, stats)) = result {
+                            println!("  part {part}: {stats}");
+                        }
+                    }
+                }
+                for err in parts
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(part, _)| part.err())
+                {
+                    eprintln!("  {err}");
+                }
+            }
+            Err(err) if err.stage == Stage::Input => {
+                println!("{name}: input not available");
+                eprintln!("  {err}");
+            }
+            Err(err) => {
+                println!(
+                    "{name}: error, error (parse {})",
+                    format_duration(parse_stats.min)
+                );
+                eprintln!("  {err}");
+            }
+        }
     }
     println!("Total time: {}", format_duration(total));
+    println!("CPU time: {}", format_duration(cpu_time));
+}
+
+/// One day's results for machine readable output
+struct Record<'a> {
+    name: &'a str,
+    answers: [Option<String>; 2],
+    // Parse, part 1 and part 2, in nanoseconds
+    durations: [Option<u128>; 3],
+    status: &'static str,
+}
+
+impl<'a> Record<'a> {
+    fn new((name, (parse_stats, parts)): &DayResult<'a>) -> Self {
+        let mut record = Record {
+            name,
+            answers: [None, None],
+            durations: [None, None, None],
+            status: "ok",
+        };
+        match parts {
+            Ok(parts) => {
+                record.durations[0] = Some(parse_stats.median.as_nanos());
+                for (i, part) in parts.iter().enumerate() {
+                    let Some((part, stats)) = part else {
+                        continue;
+                    };
+                    record.answers[i] = part.as_ref().ok().map(Answer::to_string);
+                    record.durations[i + 1] = Some(stats.median.as_nanos());
+                    if part.is_err() {
+                        record.status = "error";
+                    }
+                }
+            }
+            Err(err) if err.stage == Stage::Input => record.status = "missing",
+            Err(_) => {
+                record.durations[0] = Some(parse_stats.median.as_nanos());
+                record.status = "error";
+            }
+        }
+        record
+    }
 }
 
-fn main() {
-    let args: Vec<_> = std::env::args()
-        .skip(1)
-        .map(|s| s.parse::<i32>().map_or_else(|_| s, |n| format!("d{n:02}")))
+fn json_string(s: &str) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => write!(out, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn format_json(records: &[Record]) -> String {
+    let null = || "null".to_string();
+    let rows: Vec<_> = records
+        .iter()
+        .map(|r| {
+            let [part1, part2] = r
+                .answers
+                .each_ref()
+                .map(|a| a.as_deref().map_or_else(null, json_string));
+            let [parse, time1, time2] = r.durations.map(|d| d.map_or_else(null, |d| d.to_string()));
+            let fields = [
+                ("name", json_string(r.name)),
+                ("part1", part1),
+                ("part2", part2),
+                ("parse_ns", parse),
+                ("part1_ns", time1),
+                ("part2_ns", time2),
+                ("status", json_string(r.status)),
+            ];
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(key, value)| format!("\"{key}\": {value}"))
+                .collect();
+            format!("  {{{}}}", fields.join(", "))
+        })
         .collect();
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn format_csv(records: &[Record]) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = String::from("name,part1,part2,parse_ns,part1_ns,part2_ns,status\n");
+    for r in records {
+        let [part1, part2] = r
+            .answers
+            .each_ref()
+            .map(|a| csv_field(a.as_deref().unwrap_or_default()));
+        let [parse, time1, time2] = r
+            .durations
+            .map(|d| d.map(|d| d.to_string()).unwrap_or_default());
+        writeln!(
+            out,
+            "{},{part1},{part2},{parse},{time1},{time2},{}",
+            r.name, r.status
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn format_markdown(records: &[Record]) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = String::from(
+        "| Day | Part 1 | Part 2 | Parse | Part 1 time | Part 2 time | Status |\n\
+         | --- | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for r in records {
+        let [part1, part2] = r
+            .answers
+            .each_ref()
+            .map(|a| a.as_deref().unwrap_or("-").replace('|', "\\|"));
+        let [parse, time1, time2] = r.durations.map(|d| {
+            d.map_or_else(
+                || "-".to_string(),
+                |d| format_duration(Duration::from_nanos(d.try_into().unwrap_or(u64::MAX))),
+            )
+        });
+        writeln!(
+            out,
+            "| {} | {part1} | {part2} | {parse} | {time1} | {time2} | {} |",
+            r.name, r.status
+        )
+        .unwrap();
+    }
+    out
+}
+
+// Solves each day and compares the answers against the known ones. Fails if any answer is wrong
+// or any part could not be solved, even without a known answer
+fn verify_days(days: &[Selected]) -> Result<(), String> {
+    let answers = Answers::load(ANSWERS_FILE).map_err(|e| format!("answers not available, {e}"))?;
+
+    let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+    for &selected in days {
+        let day = selected.day;
+        let results = match solve_day(selected, Sampling::default()).1 {
+            Ok(parts) => parts.map(|part| part.map(|(result, _)| result)),
+            Err(err) => selected
+                .parts
+                .0
+                .map(|solve| solve.then(|| Err(err.clone()))),
+        };
+        for (part, result) in (1..).zip(results) {
+            let Some(result) = result else {
+                continue;
+            };
+            let verdict = answers.check(&day.name, part, result.as_ref().ok());
+            println!(
+                "{} part {part}: {} ({verdict})",
+                day.name,
+                format_answer(&result)
+            );
+            match verdict {
+                Verdict::Pass => passed += 1,
+                Verdict::Fail { .. } => failed += 1,
+                Verdict::Unknown if result.is_err() => failed += 1,
+                Verdict::Unknown => unknown += 1,
+            }
+            if let Err(err) = result {
+                eprintln!("  {err}");
+            }
+        }
+    }
+
+    println!("{passed} passed, {failed} failed, {unknown} unknown");
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(format!("{failed} parts failed"))
+    }
+}
+
+// Converts a file between plaintext and the encrypted format used in data/. Either path can be "-"
+// for stdin or stdout
+fn convert_file(input: &str, output: &str, encrypt: bool) -> Result<(), String> {
+    let data = if input == "-" {
+        let mut data = Vec::new();
+        io::stdin()
+            .read_to_end(&mut data)
+            .map(|_| data)
+            .map_err(|e| format!("could not read stdin: {e}"))?
+    } else {
+        fs::read(input).map_err(|e| format!("could not read {input}: {e}"))?
+    };
+
+    let key = Key::from_env().map_err(|e| e.to_string())?;
+    let data = if encrypt {
+        key.encrypt(&data).map(String::into_bytes)
+    } else {
+        key.decrypt(&data)
+    }
+    .map_err(|e| e.to_string())?;
+
+    if output == "-" {
+        io::stdout()
+            .write_all(&data)
+            .map_err(|e| format!("could not write stdout: {e}"))
+    } else {
+        fs::write(output, data).map_err(|e| format!("could not write {output}: {e}"))
+    }
+}
+
+// Downloads the input of each day that does not have one yet
+fn fetch_days(days: &[u32]) -> Result<(), String> {
+    let fetcher = Fetcher::from_env().map_err(|e| e.to_string())?;
+    let mut failed = 0;
+    for &day in days {
+        let name = format!("d{day:02}");
+        match fetcher.fetch_missing(day, Path::new(&format!("data/{name}.txt"))) {
+            Ok(Fetched::Downloaded) => println!("{name}: downloaded"),
+            Ok(Fetched::AlreadyPresent) => println!("{name}: already present"),
+            Err(e) => {
+                println!("{name}: failed");
+                eprintln!("  {e}");
+                failed += 1;
+            }
+        }
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(format!("{failed} inputs could not be downloaded"))
+    }
+}
+
+// Shows each selected day, its input file and whether the input is there
+fn list_days(days: &[Selected]) {
+    for &Selected { day, .. } in days {
+        let status = match fs::read(&day.filename) {
+            Ok(data) if crypt::is_encrypted(&data) => "present, encrypted",
+            Ok(_) => "present",
+            Err(_) => "missing",
+        };
+        println!("{}: {} ({status})", day.name, day.filename);
+    }
+}
+
+// Parses a day as `7`, `07` or `d07`
+fn parse_day(s: &str) -> Result<u32, String> {
+    s.strip_prefix('d')
+        .unwrap_or(s)
+        .parse()
+        .map_err(|_| format!("invalid day {s:?}"))
+}
+
+// Parses a day or a range of days like `1-5`
+fn parse_days(days: &str) -> Result<RangeInclusive<u32>, String> {
+    let range = match days.split_once('-') {
+        Some((first, last)) => parse_day(first)?..=parse_day(last)?,
+        None => parse_day(days).map(|day| day..=day)?,
+    };
+    if range.is_empty() {
+        return Err(format!("empty range of days {days:?}"));
+    }
+    Ok(range)
+}
+
+// Parses a day or a range of days, optionally followed by a part like `:2`
+fn parse_selector(arg: &str) -> Result<(RangeInclusive<u32>, Parts), String> {
+    let (days, parts) = match arg.split_once(':') {
+        Some((days, "1")) => (days, Parts::only(1)),
+        Some((days, "2")) => (days, Parts::only(2)),
+        Some((_, part)) => {
+            return Err(format!("invalid part {part:?} in {arg:?}, expected 1 or 2"));
+        }
+        None => (arg, Parts::BOTH),
+    };
+    Ok((parse_days(days)?, parts))
+}
+
+// Picks puzzle days to download inputs for. Unlike `select_days` these do not need a module yet
+fn select_puzzles(args: &[String]) -> Result<Vec<u32>, String> {
+    if args.is_empty() {
+        return Ok(PUZZLE_DAYS.collect());
+    }
+    let mut days = Vec::new();
+    for arg in args {
+        let range = parse_days(arg)?;
+        if *range.start() == 0 || *range.end() > MAX_DAY {
+            return Err(format!(
+                "no puzzle for {arg:?}, days go from 1 to {MAX_DAY}"
+            ));
+        }
+        days.extend(range);
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+// Picks the days named by `args`, in the order they are registered. Every day named must exist
+fn select_days(args: &[String]) -> Result<Vec<Selected<'static>>, String> {
+    if args.is_empty() {
+        return Ok(DAYS
+            .iter()
+            .map(|day| Selected {
+                day,
+                parts: Parts::BOTH,
+            })
+            .collect());
+    }
+
+    let mut selected = vec![Parts::NONE; DAYS.len()];
+    for arg in args {
+        if arg.starts_with("--") {
+            return Err(format!("unknown option {arg}"));
+        }
+        let (range, parts) = parse_selector(arg)?;
+        for number in range {
+            let index = DAYS
+                .iter()
+                .position(|day| day.number() == number)
+                .ok_or_else(|| format!("unknown day {number} in {arg:?}"))?;
+            selected[index] = selected[index].union(parts);
+        }
+    }
+    Ok(DAYS
+        .iter()
+        .zip(selected)
+        .filter(|&(_, parts)| parts != Parts::NONE)
+        .map(|(day, parts)| Selected { day, parts })
+        .collect())
+}
+
+enum Command {
+    Run {
+        days: Vec<Selected<'static>>,
+        options: Options,
+    },
+    Verify(Vec<Selected<'static>>),
+    Fetch(Vec<u32>),
+    Convert {
+        input: String,
+        output: String,
+        encrypt: bool,
+    },
+    New {
+        day: u32,
+        example: Option<String>,
+    },
+    List(Vec<Selected<'static>>),
+    Help,
+}
+
+impl Command {
+    fn from_args(mut args: Vec<String>) -> Result<Self, String> {
+        // These flags take no value and can go anywhere, so they are handled before any options
+        if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+            return Ok(Command::Help);
+        }
+        if let Some(i) = args.iter().position(|arg| arg == "--list") {
+            args.remove(i);
+            return select_days(&args).map(Command::List);
+        }
 
-    let days: Vec<_> = match args.len() {
-        0 => DAYS.iter().collect(),
-        _ => DAYS.iter().filter(|day| args.contains(&day.name)).collect(),
+        let command = match args.first().map(String::as_str) {
+            Some(name @ ("encrypt" | "decrypt")) => {
+                let [_, input, output] = &args[..] else {
+                    return Err(format!("{name} expects an input and an output path"));
+                };
+                Command::Convert {
+                    input: input.clone(),
+                    output: output.clone(),
+                    encrypt: name == "encrypt",
+                }
+            }
+            Some("new") => match &args[1..] {
+                [day] => Command::New {
+                    day: parse_day(day)?,
+                    example: None,
+                },
+                [day, flag, example] if flag == "--example" => Command::New {
+                    day: parse_day(day)?,
+                    example: Some(example.clone()),
+                },
+                _ => return Err("new expects a day and optionally --example PATH".to_string()),
+            },
+            Some("fetch") => Command::Fetch(select_puzzles(&args[1..])?),
+            Some("verify") => Command::Verify(select_days(&args[1..])?),
+            _ => {
+                let options = Options::from_args(&mut args)?;
+                let days = select_days(&args)?;
+                if options.input.is_some() && days.len() != 1 {
+                    return Err("--input needs exactly one day".to_string());
+                }
+                Command::Run { days, options }
+            }
+        };
+        Ok(command)
+    }
+
+    fn run(self) -> Result<(), String> {
+        match self {
+            Command::Run { days, options } => {
+                match &options.input {
+                    Some(input) => {
+                        let day = days[0].day.with_input(input);
+                        let selected = Selected {
+                            day: &day,
+                            ..days[0]
+                        };
+                        run_days(&[selected], &options);
+                    }
+                    None => run_days(&days, &options),
+                }
+                Ok(())
+            }
+            Command::Verify(days) => verify_days(&days),
+            Command::Fetch(days) => fetch_days(&days),
+            Command::Convert {
+                input,
+                output,
+                encrypt,
+            } => convert_file(&input, &output, encrypt),
+            Command::New { day, example } => {
+                let name = scaffold::new_day(Path::new("."), day, example.as_deref())?;
+                println!("created src/days/{name}.rs");
+                Ok(())
+            }
+            Command::List(days) => {
+                list_days(&days);
+                Ok(())
+            }
+            Command::Help => {
+                println!("{USAGE}");
+                Ok(())
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+
+    // Mistakes in the arguments get the usage, anything that goes wrong later does not
+    let command = match Command::from_args(args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
     };
+    match command.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(args: &[&str]) -> Result<Vec<(String, Parts)>, String> {
+        let args: Vec<_> = args.iter().map(ToString::to_string).collect();
+        let days = select_days(&args)?;
+        Ok(days
+            .into_iter()
+            .map(|selected| (selected.day.name.clone(), selected.parts))
+            .collect())
+    }
 
-    run_days(days);
+    #[test]
+    fn selects_days() {
+        assert_eq!(select(&[]).unwrap().len(), DAYS.len());
+        assert_eq!(
+            select(&["d03", "1-2", "7:2", "2:1"]).unwrap(),
+            [
+                ("d01".to_string(), Parts::BOTH),
+                ("d02".to_string(), Parts::BOTH),
+                ("d03".to_string(), Parts::BOTH),
+                ("d07".to_string(), Parts::only(2)),
+            ]
+        );
+        assert_eq!(
+            select(&["07:1", "7:2"]).unwrap(),
+            [("d07".to_string(), Parts::BOTH)]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_days() {
+        for args in [["13"], ["x"], ["5-2"], ["1:3"], ["10-14"], ["--list"]] {
+            assert!(select(&args).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn flags_without_values() {
+        let parse =
+            |args: &[&str]| Command::from_args(args.iter().map(ToString::to_string).collect());
+        assert!(matches!(parse(&["7", "--help"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["--list", "3"]), Ok(Command::List(days)) if days.len() == 1));
+        assert!(parse(&["7", "--foo"]).is_err_and(|e| e == "unknown option --foo"));
+        assert!(parse(&["7", "--jobs"]).is_err_and(|e| e == "--jobs needs a value"));
+    }
+
+    #[test]
+    fn stdin_input() {
+        let parse =
+            |args: &[&str]| Command::from_args(args.iter().map(ToString::to_string).collect());
+        for args in [
+            &["1", "--input", "-"][..],
+            &["--input", "-", "1"],
+            &["1", "-"],
+            &["-", "1"],
+        ] {
+            let Ok(Command::Run { days, options }) = parse(args) else {
+                panic!("{args:?} should run a day");
+            };
+            assert_eq!(days.len(), 1, "{args:?}");
+            assert_eq!(options.input.as_deref(), Some("-"), "{args:?}");
+        }
+    }
+
+    fn stats() -> Stats {
+        Stats::new(vec![Duration::from_nanos(1500)])
+    }
+
+    fn error(stage: Stage) -> SolveError {
+        SolveError {
+            day: "d01".to_string(),
+            stage,
+            line: None,
+            column: None,
+            text: String::new(),
+            message: "failed".to_string(),
+        }
+    }
+
+    #[test]
+    fn sampled_total_counts_every_run() {
+        let runs = std::cell::Cell::new(0);
+        let sampling = Sampling {
+            repeat: 10,
+            min_time: Duration::ZERO,
+        };
+        let ((), stats) = sampling.run(|| {
+            runs.set(runs.get() + 1);
+            thread::sleep(Duration::from_millis(1));
+        });
+        // The first run, one warmup run and the samples
+        assert_eq!((runs.get(), stats.samples), (12, 10));
+        assert!(stats.total >= Duration::from_millis(12));
+    }
+
+    #[test]
+    fn escapes_fields() {
+        assert_eq!(json_string("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(json_string("x\ny\u{1}"), r#""x\u000ay\u0001""#);
+        assert_eq!(csv_field("1234"), "1234");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn record_status() {
+        let solved: DayResult = (
+            "d01",
+            (stats(), Ok([Some((Ok(Answer::from(3u64)), stats())), None])),
+        );
+        let record = Record::new(&solved);
+        assert_eq!(record.answers, [Some("3".to_string()), None]);
+        assert_eq!(record.durations, [Some(1500), Some(1500), None]);
+        assert_eq!(record.status, "ok");
+
+        let failed: DayResult = (
+            "d01",
+            (
+                stats(),
+                Ok([Some((Err(error(Stage::Part1)), stats())), None]),
+            ),
+        );
+        assert_eq!(Record::new(&failed).status, "error");
+
+        let missing: DayResult = ("d01", (stats(), Err(error(Stage::Input))));
+        let record = Record::new(&missing);
+        assert_eq!((record.status, record.durations[0]), ("missing", None));
+
+        let unparsed: DayResult = ("d01", (stats(), Err(error(Stage::Parse))));
+        let record = Record::new(&unparsed);
+        assert_eq!((record.status, record.durations[0]), ("error", Some(1500)));
+    }
+
+    #[test]
+    fn formats_records() {
+        let record = Record {
+            name: "d01",
+            answers: [Some("a|b,\"c\"".to_string()), None],
+            durations: [Some(1500), Some(2_000_000), None],
+            status: "ok",
+        };
+        let records = [record];
+        assert_eq!(
+            format_json(&records),
+            concat!(
+                "[\n",
+                r#"  {"name": "d01", "part1": "a|b,\"c\"", "part2": null, "#,
+                r#""parse_ns": 1500, "part1_ns": 2000000, "part2_ns": null, "status": "ok"}"#,
+                "\n]\n"
+            )
+        );
+        assert_eq!(
+            format_csv(&records).lines().nth(1),
+            Some("d01,\"a|b,\"\"c\"\"\",,1500,2000000,,ok")
+        );
+        assert_eq!(
+            format_markdown(&records).lines().nth(2),
+            Some("| d01 | a\\|b,\"c\" | - | 1.500µs | 2.000ms | - | ok |")
+        );
+    }
+
+    #[test]
+    fn selects_puzzles() {
+        let args = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(select_puzzles(&[]).unwrap(), (1..=12).collect::<Vec<_>>());
+        assert_eq!(
+            select_puzzles(&args(&["d12", "2-3", "3"])).unwrap(),
+            [2, 3, 12]
+        );
+        assert!(select_puzzles(&args(&["0"])).is_err());
+        assert_eq!(select_puzzles(&args(&["13"])).unwrap(), [13]);
+        assert!(select_puzzles(&args(&["24-26"])).is_err());
+        assert!(select_puzzles(&args(&["1:2"])).is_err());
+    }
 }
@@ -0,0 +1,84 @@
+use std::{fs, path::Path};
+
+use crate::days::read_input;
+
+pub const TEMPLATE_FILE: &str = "src/days/template.rs";
+
+// The empty example in the template, which is replaced when an example is given
+const TEMPLATE_EXAMPLE: &str = "const EXAMPLE: &str = \"\n\n\";";
+
+/// Adds day `day` to the project under `root` by writing `src/days/dNN.rs` from the template, which
+/// the build script then registers. The example test uses the contents of `example` when given,
+/// where "-" is stdin. Returns the name of the new day
+pub fn new_day(root: &Path, day: u32, example: Option<&str>) -> Result<String, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("invalid day {day}, expected 1 to 25"));
+    }
+    let name = format!("d{day:02}");
+    let module_file = root.join(format!("src/days/{name}.rs"));
+    if module_file.exists() {
+        return Err(format!("{} already exists", module_file.display()));
+    }
+
+    let template = fs::read_to_string(root.join(TEMPLATE_FILE))
+        .map_err(|e| format!("could not read {TEMPLATE_FILE}: {e}"))?;
+    let example = example
+        .map(|path| read_input(path).map_err(|e| format!("could not read {path}: {e}")))
+        .transpose()?;
+    let source = render_day(&template, example.as_deref())?;
+    fs::write(&module_file, source)
+        .map_err(|e| format!("could not write {}: {e}", module_file.display()))?;
+    Ok(name)
+}
+
+/// Fills in the example of a day made from the template
+pub fn render_day(template: &str, example: Option<&str>) -> Result<String, String> {
+    let Some(example) = example else {
+        return Ok(template.to_string());
+    };
+    if !template.contains(TEMPLATE_EXAMPLE) {
+        return Err(format!("{TEMPLATE_FILE} has no empty EXAMPLE to fill in"));
+    }
+    // Examples are trimmed before use, so surrounding blank lines do not matter
+    let example = example.trim_matches('\n');
+    let literal = if example.contains(['"', '\\']) {
+        // The raw string needs more #s than follow any quote in the example
+        let longest = example
+            .split('"')
+            .skip(1)
+            .map(|after| after.len() - after.trim_start_matches('#').len())
+            .max()
+            .unwrap_or(0);
+        let hashes = "#".repeat(longest + 1);
+        format!("r{hashes}\"\n{example}\n\"{hashes}")
+    } else {
+        format!("\"\n{example}\n\"")
+    };
+    Ok(template.replace(
+        TEMPLATE_EXAMPLE,
+        &format!("const EXAMPLE: &str = {literal};"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_in_example() {
+        let template = "const EXAMPLE: &str = \"\n\n\";\n";
+        assert_eq!(render_day(template, None).unwrap(), template);
+        assert_eq!(
+            render_day(template, Some("1 2\n3 4\n")).unwrap(),
+            "const EXAMPLE: &str = \"\n1 2\n3 4\n\";\n"
+        );
+        assert_eq!(
+            render_day(template, Some("a\"b\n")).unwrap(),
+            "const EXAMPLE: &str = r#\"\na\"b\n\"#;\n"
+        );
+        assert_eq!(
+            render_day(template, Some("\"#\"##x\n")).unwrap(),
+            "const EXAMPLE: &str = r###\"\n\"#\"##x\n\"###;\n"
+        );
+    }
+}